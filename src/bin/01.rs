@@ -1,120 +1,58 @@
+use advent_of_code::helper::matcher::Matcher;
+use advent_of_code::helper::math::Overflow;
+
 advent_of_code::solution!(1);
 
-pub fn part_one(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| {
-                let mut digits = line.chars().filter(|char| char.is_ascii_digit());
-                let first = digits.next().unwrap();
-                let mut value = first.to_string();
-                if let Some(last) = digits.last() {
-                    value.push(last);
-                } else {
-                    value.push(first);
-                }
-                value
-            })
-            .map(|strings| {
-                return strings.parse::<u32>().unwrap();
-            })
-            .sum(),
-    )
-}
+/// The tokens standing for each digit.
+type Vocabulary = [(&'static str, u32)];
 
-const ONE: [char; 3] = ['o', 'n', 'e'];
-const TWO: [char; 3] = ['t', 'w', 'o'];
-const THREE: [char; 5] = ['t', 'h', 'r', 'e', 'e'];
-const FOUR: [char; 4] = ['f', 'o', 'u', 'r'];
-const FIVE: [char; 4] = ['f', 'i', 'v', 'e'];
-const SIX: [char; 3] = ['s', 'i', 'x'];
-const SEVEN: [char; 5] = ['s', 'e', 'v', 'e', 'n'];
-const EIGHT: [char; 5] = ['e', 'i', 'g', 'h', 't'];
-const NINE: [char; 4] = ['n', 'i', 'n', 'e'];
-const DIGIT_LENGTHS: [usize; 9] = [
-    ONE.len(),
-    TWO.len(),
-    THREE.len(),
-    FOUR.len(),
-    FIVE.len(),
-    SIX.len(),
-    SEVEN.len(),
-    EIGHT.len(),
-    NINE.len(),
+const DIGITS: [(&str, u32); 9] = [
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
 ];
 
-pub fn part_two(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| {
-                let mut first: char = ' ';
-                let mut last: char = ' ';
-                let mut digit_indices: [usize; 9] = [0; 9];
-                for character in line.chars() {
-                    if character.is_ascii_digit() {
-                        if first == ' ' {
-                            first = character;
-                        }
-                        last = character;
-                        digit_indices = [0; 9];
-                    } else {
-                        for (index, digit_len) in DIGIT_LENGTHS.iter().enumerate() {
-                            if character
-                                == match index {
-                                    0 => ONE[digit_indices[index]],
-                                    1 => TWO[digit_indices[index]],
-                                    2 => THREE[digit_indices[index]],
-                                    3 => FOUR[digit_indices[index]],
-                                    4 => FIVE[digit_indices[index]],
-                                    5 => SIX[digit_indices[index]],
-                                    6 => SEVEN[digit_indices[index]],
-                                    7 => EIGHT[digit_indices[index]],
-                                    8 => NINE[digit_indices[index]],
-                                    _ => panic!("Invalid index"),
-                                }
-                            {
-                                digit_indices[index] += 1;
-                                if digit_indices[index] == *digit_len {
-                                    digit_indices[index] = 0;
-                                    let digit = (index + 1).to_string().chars().next().unwrap();
+const ENGLISH_WORDS: [(&str, u32); 9] = [
+    ("one", 1),
+    ("two", 2),
+    ("three", 3),
+    ("four", 4),
+    ("five", 5),
+    ("six", 6),
+    ("seven", 7),
+    ("eight", 8),
+    ("nine", 9),
+];
 
-                                    if first == ' ' {
-                                        first = digit;
-                                    }
-                                    last = digit;
-                                }
-                            } else {
-                                digit_indices[index] = 0;
-                                if character
-                                    == match index {
-                                        0 => ONE[digit_indices[index]],
-                                        1 => TWO[digit_indices[index]],
-                                        2 => THREE[digit_indices[index]],
-                                        3 => FOUR[digit_indices[index]],
-                                        4 => FIVE[digit_indices[index]],
-                                        5 => SIX[digit_indices[index]],
-                                        6 => SEVEN[digit_indices[index]],
-                                        7 => EIGHT[digit_indices[index]],
-                                        8 => NINE[digit_indices[index]],
-                                        _ => panic!("Invalid index"),
-                                    }
-                                {
-                                    digit_indices[index] += 1;
-                                }
-                            }
-                        }
-                    }
-                }
-                let mut value = first.to_string();
-                value.push(last);
-                value
-            })
-            .map(|strings| {
-                return strings.parse::<u32>().unwrap();
-            })
-            .sum(),
-    )
+/// Sums the calibration value of each line: its first and last digit, as a two-digit number.
+/// Returns [`None`] if a line doesn't contain any digit.
+fn calibration_sum(input: &str, vocabularies: &[&Vocabulary]) -> Result<Option<u64>, Overflow> {
+    let (tokens, digits): (Vec<_>, Vec<_>) =
+        vocabularies.iter().copied().flatten().copied().unzip();
+    let matcher = Matcher::new(tokens);
+    let mut sum = 0_u64;
+    for line in input.lines() {
+        let (Some(first), Some(last)) = (matcher.first(line), matcher.last(line)) else {
+            return Ok(None);
+        };
+        let value = digits[first.pattern] * 10 + digits[last.pattern];
+        sum = sum.checked_add(value.into()).ok_or(Overflow)?;
+    }
+    Ok(Some(sum))
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    calibration_sum(input, &[&DIGITS]).unwrap()
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    calibration_sum(input, &[&DIGITS, &ENGLISH_WORDS]).unwrap()
 }
 
 #[cfg(test)]
@@ -132,4 +70,48 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(93));
     }
+
+    #[test]
+    fn test_overlapping_words() {
+        for (line, expected) in [
+            ("oneight", 18),
+            ("sevenine", 79),
+            ("twone", 21),
+            ("eightwo", 82),
+            ("xtwone3four", 24),
+            ("4nineeightseven2", 42),
+            ("zoneight234", 14),
+            ("7pqrstsixteen", 76),
+            ("nineight", 98),
+            ("5", 55),
+        ] {
+            assert_eq!(part_two(line), Some(expected), "{line}");
+        }
+    }
+
+    #[test]
+    fn test_missing_digit() {
+        assert_eq!(part_one("1abc2\nnone"), None);
+        assert_eq!(part_two("one\nxyz"), None);
+    }
+
+    #[test]
+    fn test_custom_vocabulary() {
+        const GERMAN_WORDS: [(&str, u32); 9] = [
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("vier", 4),
+            ("fünf", 5),
+            ("sechs", 6),
+            ("sieben", 7),
+            ("acht", 8),
+            ("neun", 9),
+        ];
+        let input = "zweins\nxfünfzig7\nsiebenacht";
+        assert_eq!(
+            calibration_sum(input, &[&DIGITS, &GERMAN_WORDS]),
+            Ok(Some(21 + 57 + 78))
+        );
+    }
 }
@@ -1,39 +1,39 @@
+use std::collections::HashMap;
 use std::str::FromStr;
 
+use advent_of_code::helper::math::{CheckedIterator, Overflow};
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
+
 advent_of_code::solution!(2);
 
-#[derive(Debug)]
-struct Hand {
-    red: u32,
-    green: u32,
-    blue: u32,
-}
+/// The cubes in the bag for part one.
+const BAG: [(&str, u32); 3] = [("red", 12), ("green", 13), ("blue", 14)];
 
-#[derive(Debug, PartialEq, Eq)]
-struct ParseHandError;
+/// The colours which make up the power of a game in part two.
+const COLOURS: [&str; 3] = ["red", "green", "blue"];
 
-impl FromStr for Hand {
-    type Err = ParseHandError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut hand = Hand {
-            red: 0,
-            green: 0,
-            blue: 0,
-        };
-        for color in s.split(",") {
-            let color = color.trim();
-            if let Some((value, color)) = color.split_once(" ") {
-                let value = value.parse::<u32>().map_err(|_| ParseHandError)?;
-                match color {
-                    "red" => hand.red = value,
-                    "green" => hand.green = value,
-                    "blue" => hand.blue = value,
-                    _ => return Err(ParseHandError),
-                }
-            } else {
-                return Err(ParseHandError);
+/// Counts of cubes by colour. Colours which aren't mentioned have no cubes.
+#[derive(Debug, Default, PartialEq)]
+struct Hand(HashMap<String, u32>);
+
+impl Hand {
+    fn count(&self, colour: &str) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut hand = Hand::default();
+        parser.separated(",", |parser| {
+            let value = parser.number::<u32>()?;
+            parser.skip_spaces();
+            let colour_start = *parser;
+            let colour = parser.word()?;
+            if hand.0.insert(colour.to_string(), value).is_some() {
+                return Err(colour_start.error("a colour not already in the hand"));
             }
-        }
+            Ok(())
+        })?;
         Ok(hand)
     }
 }
@@ -42,94 +42,87 @@ impl FromStr for Hand {
 struct Game {
     id: u32,
     hands: Vec<Hand>,
-    max_hand: Hand,
-    min_hand: Hand,
 }
 
-#[derive(Debug, PartialEq, Eq)]
-struct ParseGameError;
+impl Game {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let (id, hands) = parser.key_value(
+            ":",
+            |parser| {
+                parser.literal("Game")?;
+                parser.number::<u32>()
+            },
+            |parser| parser.separated(";", Hand::parse),
+        )?;
+        parser.end()?;
+        Ok(Game { id, hands })
+    }
+
+    /// The largest count of each colour over all hands, i.e. the fewest cubes the game needs.
+    fn max_hand(&self) -> Hand {
+        let mut max_hand = Hand::default();
+        for (colour, &count) in self.hands.iter().flat_map(|hand| hand.0.iter()) {
+            let max = max_hand.0.entry(colour.clone()).or_default();
+            *max = (*max).max(count);
+        }
+        max_hand
+    }
+
+    /// Whether every hand could have been drawn from `bag`.
+    fn is_possible(&self, bag: &[(&str, u32)]) -> bool {
+        self.max_hand().0.iter().all(|(colour, &count)| {
+            let available = bag.iter().find(|(c, _)| c == colour).map_or(0, |&(_, n)| n);
+            count <= available
+        })
+    }
+
+    /// The product of the fewest cubes of each of `colours` the game needs.
+    fn power(&self, colours: &[&str]) -> Result<u64, Overflow> {
+        let max_hand = self.max_hand();
+        colours
+            .iter()
+            .map(|colour| u64::from(max_hand.count(colour)))
+            .checked_product()
+    }
+}
 
 impl FromStr for Game {
-    type Err = ParseGameError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut game = Game {
-            id: 0,
-            hands: Vec::new(),
-            max_hand: Hand {
-                red: 0,
-                green: 0,
-                blue: 0,
-            },
-            min_hand: Hand {
-                red: 0,
-                green: 0,
-                blue: 0,
-            },
-        };
-        if let Some((id_string, hands)) = s.split_once(":") {
-            if let Some((_, id)) = id_string.split_once(" ") {
-                game.id = id.parse::<u32>().map_err(|_| ParseGameError)?;
-            } else {
-                return Err(ParseGameError);
-            }
-            for hand in hands.split(";") {
-                let hand = hand.parse::<Hand>().map_err(|_| ParseGameError)?;
-                if hand.red > game.max_hand.red {
-                    game.max_hand.red = hand.red;
-                }
-                if hand.green > game.max_hand.green {
-                    game.max_hand.green = hand.green;
-                }
-                if hand.blue > game.max_hand.blue {
-                    game.max_hand.blue = hand.blue;
-                }
-                if hand.red > game.min_hand.red || game.min_hand.red == 0 {
-                    game.min_hand.red = hand.red;
-                }
-                if hand.green > game.min_hand.green || game.min_hand.green == 0 {
-                    game.min_hand.green = hand.green;
-                }
-                if hand.blue > game.min_hand.blue || game.min_hand.blue == 0 {
-                    game.min_hand.blue = hand.blue;
-                }
-                game.hands.push(hand);
-            }
-        } else {
-            return Err(ParseGameError);
-        }
-        Ok(game)
+        Game::parse(&mut Parser::new(s))
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    const RED_AMOUNT: u32 = 12;
-    const GREEN_AMOUNT: u32 = 13;
-    const BLUE_AMOUNT: u32 = 14;
-    input
+fn parse_games(input: &str) -> Result<Vec<Game>, ParseError> {
+    Parser::new(input)
         .lines()
-        .map(|line| line.parse::<Game>())
-        .filter(|game| game.is_ok())
-        .map(|game| game.unwrap())
-        .filter(|game| {
-            game.max_hand.red <= RED_AMOUNT
-                && game.max_hand.green <= GREEN_AMOUNT
-                && game.max_hand.blue <= BLUE_AMOUNT
-        })
-        .map(|game| game.id)
-        .sum::<u32>()
-        .into()
+        .map(|mut line| Game::parse(&mut line))
+        .collect()
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    input
-        .lines()
-        .map(|line| line.parse::<Game>())
-        .filter(|game| game.is_ok())
-        .map(|game| game.unwrap())
-        .map(|game| game.min_hand.red * game.min_hand.green * game.min_hand.blue)
-        .sum::<u32>()
-        .into()
+fn sum_of_possible_games(games: &[Game], bag: &[(&str, u32)]) -> Result<u64, Overflow> {
+    games
+        .iter()
+        .filter(|game| game.is_possible(bag))
+        .map(|game| u64::from(game.id))
+        .checked_sum()
+}
+
+fn sum_of_powers(games: &[Game], colours: &[&str]) -> Result<u64, Overflow> {
+    games.iter().try_fold(0_u64, |sum, game| {
+        sum.checked_add(game.power(colours)?).ok_or(Overflow)
+    })
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let games = parse_games(input).unwrap();
+    Some(sum_of_possible_games(&games, &BAG).unwrap())
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    let games = parse_games(input).unwrap();
+    Some(sum_of_powers(&games, &COLOURS).unwrap())
 }
 
 #[cfg(test)]
@@ -147,4 +140,64 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(2286));
     }
+
+    #[test]
+    fn test_game_parse() {
+        let game = "Game 7: 3 blue, 4 red; 1 red, 2 green"
+            .parse::<Game>()
+            .unwrap();
+        assert_eq!(game.id, 7);
+        assert_eq!(game.hands.len(), 2);
+        assert_eq!(game.max_hand().count("red"), 4);
+        assert_eq!(game.max_hand().count("green"), 2);
+        assert_eq!(game.max_hand().count("blue"), 3);
+        assert_eq!(game.max_hand().count("purple"), 0);
+    }
+
+    #[test]
+    fn test_game_parse_error() {
+        let error = "Game 7: 3 blue, 4 red, 5 blue".parse::<Game>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 26));
+        assert_eq!(error.expected, "a colour not already in the hand");
+
+        let error = "Game 7 3 blue".parse::<Game>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 8));
+        assert_eq!(error.expected, "`:`");
+    }
+
+    #[test]
+    fn test_parse_error_line() {
+        let input = "Game 1: 3 blue\nGame 2: 4 red\nGame 3 5 green\n";
+        let error = parse_games(input).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 8));
+    }
+
+    #[test]
+    fn test_other_colours_and_bags() {
+        let games = parse_games(
+            "Game 1: 3 blue, 2 purple; 1 red\nGame 2: 4 red, 1 green\nGame 3: 1 purple",
+        )
+        .unwrap();
+        assert_eq!(sum_of_possible_games(&games, &BAG), Ok(2));
+        assert_eq!(
+            sum_of_possible_games(&games, &[("red", 4), ("blue", 3), ("purple", 2)]),
+            Ok(4)
+        );
+        assert_eq!(
+            sum_of_possible_games(&games, &[("red", 4), ("green", 1)]),
+            Ok(2)
+        );
+        assert_eq!(sum_of_powers(&games, &["red", "purple"]), Ok(2));
+        assert_eq!(sum_of_powers(&games, &["purple"]), Ok(3));
+    }
+
+    #[test]
+    fn test_power_overflow() {
+        let games = parse_games("Game 1: 4294967295 red, 4294967295 blue, 2 green").unwrap();
+        assert_eq!(
+            sum_of_powers(&games, &["red", "blue"]),
+            Ok(u64::from(u32::MAX) * u64::from(u32::MAX))
+        );
+        assert_eq!(sum_of_powers(&games, &COLOURS), Err(Overflow));
+    }
 }
@@ -1,317 +1,335 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
+use std::str::FromStr;
+
+use advent_of_code::helper::geom::{Direction, Point2};
+use advent_of_code::helper::math::{CheckedIterator, Overflow};
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
 
 advent_of_code::solution!(3);
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Position(i32, i32);
-impl Position {
-    fn search_coordinates(&self) -> impl Iterator<Item = Position> + '_ {
-        let mut coordinates = Vec::new();
-        // previous row
-        for i in 0..3 {
-            coordinates.push(Position(self.0 - 1 + i as i32, self.1 - 1));
-        }
-        // current row
-        coordinates.push(Position(self.0 - 1, self.1));
-        coordinates.push(Position(self.0 + 1, self.1));
-        // next row
-        for i in 0..3 {
-            coordinates.push(Position(self.0 - 1 + i as i32, self.1 + 1));
-        }
-        coordinates.into_iter().filter(|p| p.0 >= 0 && p.1 >= 0)
-    }
-}
+type Position = Point2<usize>;
 
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug, PartialEq, Eq)]
 struct Number {
     position: Position,
-    value: u32,
-    length: u32,
+    value: u64,
+    length: usize,
 }
-impl Number {
-    fn search_coordinates(&self) -> impl Iterator<Item = Position> + '_ {
-        let mut coordinates = Vec::new();
-        let Position(x, y) = self.position;
-        // previous row
-        for i in 0..self.length + 2 {
-            coordinates.push(Position(x - 1 + i as i32, y - 1));
-        }
-        // current row
-        coordinates.push(Position(x - 1, y));
-        coordinates.push(Position(x + self.length as i32, y));
-        // next row
-        for i in 0..self.length + 2 {
-            coordinates.push(Position(x - 1 + i as i32, y + 1));
-        }
-        coordinates.into_iter().filter(|p| p.0 >= 0 && p.1 >= 0)
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Symbol(char),
+    /// A digit of the number with this index in `Schematic::numbers`.
+    Number(u32),
 }
 
 #[derive(Debug, PartialEq, Eq)]
 struct Schematic {
-    symbols: HashMap<Position, char>,
-    numbers: HashMap<Position, Number>,
-    all_number_positions: HashMap<Position, Number>,
+    width: usize,
+    /// The cells, row by row.
+    cells: Vec<Cell>,
+    numbers: Vec<Number>,
 }
 
-#[derive(Debug)]
-struct SchematicParseError;
+impl Schematic {
+    fn cell(&self, position: Position) -> Cell {
+        position
+            .to_index(self.width)
+            .and_then(|index| self.cells.get(index).copied())
+            .unwrap_or(Cell::Empty)
+    }
+
+    /// The cells of the number and of its neighbours, row by row. Doesn't allocate.
+    fn cells_around(&self, number: &Number) -> impl Iterator<Item = Cell> + '_ {
+        let Position { x, y } = number.position;
+        let height = self.cells.len() / self.width.max(1);
+        let columns = x.saturating_sub(1)..(x + number.length + 1).min(self.width);
+        (y.saturating_sub(1)..(y + 2).min(height)).flat_map(move |row| {
+            self.cells[row * self.width..][columns.clone()]
+                .iter()
+                .copied()
+        })
+    }
+
+    fn symbols(&self) -> impl Iterator<Item = (Position, char)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &cell)| match cell {
+                Cell::Symbol(symbol) => Some((Position::from_index(index, self.width)?, symbol)),
+                _ => None,
+            })
+    }
+}
 
 impl FromStr for Schematic {
-    type Err = SchematicParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut symbols = HashMap::new();
-        let mut numbers = HashMap::new();
-        let mut all_number_positions = HashMap::new();
-        let mut digit_accumulator = [' '; 10];
-        let mut digit_accumulator_index: usize = 0;
-        let mut parsing_digit = false;
-        let mut current_position = Position(0, 0);
-        for (y, line) in s.lines().enumerate() {
-            for (x, symbol) in line.chars().enumerate() {
-                current_position = Position(x as i32, y as i32);
-                match symbol {
-                    '0'..='9' => {
-                        if !parsing_digit {
-                            digit_accumulator = [' '; 10];
-                            digit_accumulator_index = 0;
-                            parsing_digit = true;
-                        }
-                        digit_accumulator[digit_accumulator_index] = symbol;
-                        digit_accumulator_index += 1;
-                    }
-                    _ => {
-                        if symbol != '.' {
-                            symbols.insert(current_position, symbol);
-                        }
-                        if parsing_digit {
-                            let value = digit_accumulator
-                                .iter()
-                                .take_while(|&&c| c != ' ')
-                                .collect::<String>()
-                                .parse::<u32>()
-                                .unwrap();
-                            let number_position = Position(
-                                current_position.0 as i32 - digit_accumulator_index as i32,
-                                current_position.1 as i32,
-                            );
-                            numbers.insert(
-                                number_position,
-                                Number {
-                                    position: number_position,
-                                    value,
-                                    length: digit_accumulator_index as u32,
-                                },
-                            );
-                            // add the number to all of its positions
-                            for i in 0..digit_accumulator_index {
-                                let position = Position(
-                                    current_position.0 as i32 - 1 - i as i32,
-                                    current_position.1 as i32,
-                                );
-                                all_number_positions.insert(
-                                    position,
-                                    Number {
-                                        position: number_position,
-                                        value,
-                                        length: digit_accumulator_index as u32,
-                                    },
-                                );
-                            }
-                            parsing_digit = false;
-                        }
+        let width = s.lines().next().map_or(0, str::len);
+        let mut cells = Vec::with_capacity(s.len());
+        let mut numbers = Vec::new();
+        for (y, line) in Parser::new(s).lines().enumerate() {
+            // the line is scanned byte by byte, the parser only locates errors. Every valid cell
+            // is a single byte, so the column of a cell is its index in the line.
+            let error_at = |x: usize, expected: &str| {
+                let mut at = line;
+                (0..x).for_each(|_| _ = at.next_char());
+                at.error(expected)
+            };
+            let bytes = line.rest().as_bytes();
+            let mut x = 0;
+            while x < bytes.len() {
+                let cell = match bytes[x] {
+                    b'0'..=b'9' => {
+                        let length = bytes[x..].iter().take_while(|b| b.is_ascii_digit()).count();
+                        let digits = &line.rest()[x..x + length];
+                        let value = digits
+                            .parse::<u64>()
+                            .map_err(|_| error_at(x, "a number fitting in `u64`"))?;
+                        numbers.push(Number {
+                            position: Position::new(x, y),
+                            value,
+                            length,
+                        });
+                        let id = numbers.len() as u32 - 1;
+                        cells.extend((0..length).map(|_| Cell::Number(id)));
+                        x += length;
+                        continue;
                     }
-                }
+                    b'.' => Cell::Empty,
+                    symbol if symbol.is_ascii_punctuation() => Cell::Symbol(symbol as char),
+                    _ => return Err(error_at(x, "a digit, `.` or a symbol")),
+                };
+                cells.push(cell);
+                x += 1;
             }
-            if parsing_digit {
-                let value = digit_accumulator
-                    .iter()
-                    .take_while(|&&c| c != ' ')
-                    .collect::<String>()
-                    .parse::<u32>()
-                    .unwrap();
-                let number_position = Position(
-                    current_position.0 as i32 - digit_accumulator_index as i32 + 1,
-                    current_position.1 as i32,
-                );
-                numbers.insert(
-                    number_position,
-                    Number {
-                        position: number_position,
-                        value,
-                        length: digit_accumulator_index as u32,
-                    },
-                );
-                // add the number to all of its positions
-                for i in 0..digit_accumulator_index {
-                    let position = Position(
-                        current_position.0 as i32 - i as i32,
-                        current_position.1 as i32,
-                    );
-                    all_number_positions.insert(
-                        position,
-                        Number {
-                            position: number_position,
-                            value,
-                            length: digit_accumulator_index as u32,
-                        },
-                    );
-                }
-                parsing_digit = false;
+            if x != width {
+                let expected = format!("{width} cells per line, like the first line");
+                return Err(error_at(x.min(width), &expected));
             }
         }
         Ok(Schematic {
-            symbols,
+            width,
+            cells,
             numbers,
-            all_number_positions,
         })
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
+/// Sums the numbers next to a symbol.
+fn sum_of_part_numbers(schematic: &Schematic) -> Result<u64, Overflow> {
+    schematic
+        .numbers
+        .iter()
+        .filter(|number| {
+            schematic
+                .cells_around(number)
+                .any(|cell| matches!(cell, Cell::Symbol(_)))
+        })
+        .map(|number| number.value)
+        .checked_sum()
+}
+
+/// Sums the products of the two numbers around each `*` next to exactly two numbers.
+fn sum_of_gear_ratios(schematic: &Schematic) -> Result<u64, Overflow> {
+    schematic
+        .symbols()
+        .filter(|&(_, symbol)| symbol == '*')
+        .filter_map(|(position, _)| {
+            // the two numbers around the gear, if there are exactly two.
+            let mut ids = [None; 2];
+            for neighbour in position.neighbours(Direction::ALL) {
+                let Cell::Number(id) = schematic.cell(neighbour) else {
+                    continue;
+                };
+                match ids {
+                    [None, _] => ids[0] = Some(id),
+                    [Some(first), None] if first != id => ids[1] = Some(id),
+                    [Some(first), Some(second)] if first != id && second != id => return None,
+                    _ => {}
+                }
+            }
+            match ids {
+                [Some(first), Some(second)] => Some(
+                    schematic.numbers[first as usize]
+                        .value
+                        .checked_mul(schematic.numbers[second as usize].value)
+                        .ok_or(Overflow),
+                ),
+                _ => None,
+            }
+        })
+        .try_fold(0_u64, |sum, ratio| sum.checked_add(ratio?).ok_or(Overflow))
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
     let schematic = input.parse::<Schematic>().unwrap();
-    Some(
-        schematic
-            .numbers
-            .iter()
-            .filter(|(_, number)| {
-                number
-                    .search_coordinates()
-                    .any(|position| schematic.symbols.contains_key(&position))
-            })
-            .map(|(_, number)| number.value)
-            .sum::<u32>(),
-    )
+    Some(sum_of_part_numbers(&schematic).unwrap())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_two(input: &str) -> Option<u64> {
     let schematic = input.parse::<Schematic>().unwrap();
-    Some(
-        schematic
-            .symbols
-            .iter()
-            .filter(|(_, symbol)| **symbol == '*')
-            .filter_map(|(position, _)| {
-                // find all numbers that overlap with this position
-                let mut overlapping_numbers = HashSet::new();
-                for p in position.search_coordinates() {
-                    if let Some(number) = schematic.all_number_positions.get(&p) {
-                        if !overlapping_numbers.contains(number) {
-                            overlapping_numbers.insert(number);
-                        }
-                    }
-                }
-                if overlapping_numbers.len() == 2 {
-                    Some(
-                        overlapping_numbers
-                            .iter()
-                            .map(|number| number.value)
-                            .fold(1, |acc, value| acc * value),
-                    )
-                } else {
-                    None
-                }
-            })
-            .sum::<u32>(),
-    )
+    Some(sum_of_gear_ratios(&schematic).unwrap())
 }
 
 #[cfg(test)]
 mod tests {
+    use advent_of_code::template::input_gen::generate;
+
     use super::*;
 
     #[test]
-    fn test_search_coordinates_position() {
-        let position = Position(1, 1);
+    fn test_neighbours_position() {
+        let position = Position::new(1, 1);
 
         let expected_coordinates: Vec<Position> = [
-            Position(0, 0),
-            Position(1, 0),
-            Position(2, 0),
-            Position(0, 1),
-            Position(2, 1),
-            Position(0, 2),
-            Position(1, 2),
-            Position(2, 2),
+            Position::new(0, 0),
+            Position::new(1, 0),
+            Position::new(2, 0),
+            Position::new(0, 1),
+            Position::new(2, 1),
+            Position::new(0, 2),
+            Position::new(1, 2),
+            Position::new(2, 2),
         ]
-        .iter()
-        .cloned()
-        .collect();
+        .to_vec();
 
-        let coordinates: Vec<Position> = position.search_coordinates().collect();
+        let mut coordinates: Vec<Position> = position.neighbours(Direction::ALL).collect();
+        coordinates.sort_by_key(|p| (p.y, p.x));
 
         assert_eq!(coordinates, expected_coordinates);
     }
 
     #[test]
-    fn test_search_coordinates_number() {
-        let number = Number {
-            position: Position(1, 1),
-            value: 1,
-            length: 1,
+    fn test_cells_around_number() {
+        let schematic = "$1.\n#2*\n.34\n".parse::<Schematic>().unwrap();
+        let cells_around = |index: usize| {
+            schematic
+                .cells_around(&schematic.numbers[index])
+                .collect::<Vec<_>>()
         };
+        // a number in the middle of the grid.
+        assert_eq!(
+            cells_around(1),
+            vec![
+                Cell::Symbol('$'),
+                Cell::Number(0),
+                Cell::Empty,
+                Cell::Symbol('#'),
+                Cell::Number(1),
+                Cell::Symbol('*'),
+                Cell::Empty,
+                Cell::Number(2),
+                Cell::Number(2),
+            ]
+        );
+        // a longer number against the bottom right corner.
+        assert_eq!(
+            cells_around(2),
+            vec![
+                Cell::Symbol('#'),
+                Cell::Number(1),
+                Cell::Symbol('*'),
+                Cell::Empty,
+                Cell::Number(2),
+                Cell::Number(2),
+            ]
+        );
+    }
 
-        let expected_coordinates: Vec<Position> = [
-            Position(0, 0),
-            Position(1, 0),
-            Position(2, 0),
-            Position(0, 1),
-            Position(2, 1),
-            Position(0, 2),
-            Position(1, 2),
-            Position(2, 2),
-        ]
-        .iter()
-        .cloned()
-        .collect();
-
-        let coordinates: Vec<Position> = number.search_coordinates().collect();
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(4432));
+    }
 
-        assert_eq!(coordinates, expected_coordinates);
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(453825));
     }
 
     #[test]
-    fn test_search_coordinates_longer_number() {
-        let number = Number {
-            position: Position(1, 1),
-            value: 1,
-            length: 2,
-        };
+    fn test_generated_input() {
+        let input = generate(DAY, 200, 1).unwrap();
+        assert_eq!(part_one(&input), Some(506018));
+        assert_eq!(part_two(&input), Some(2392881));
+    }
 
-        let expected_coordinates: Vec<Position> = [
-            Position(0, 0),
-            Position(1, 0),
-            Position(2, 0),
-            Position(3, 0),
-            Position(0, 1),
-            Position(3, 1),
-            Position(0, 2),
-            Position(1, 2),
-            Position(2, 2),
-            Position(3, 2),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+    #[test]
+    fn test_schematic_parse() {
+        let schematic = "12345678901234*\n..7.........99.\n"
+            .parse::<Schematic>()
+            .unwrap();
+        assert_eq!(
+            schematic.numbers,
+            vec![
+                Number {
+                    position: Position::new(0, 0),
+                    value: 12345678901234,
+                    length: 14,
+                },
+                Number {
+                    position: Position::new(2, 1),
+                    value: 7,
+                    length: 1,
+                },
+                Number {
+                    position: Position::new(12, 1),
+                    value: 99,
+                    length: 2,
+                },
+            ]
+        );
+        assert_eq!(schematic.cell(Position::new(13, 0)), Cell::Number(0));
+        assert_eq!(schematic.cell(Position::new(13, 1)), Cell::Number(2));
+        assert_eq!(schematic.cell(Position::new(11, 1)), Cell::Empty);
+        assert_eq!(schematic.cell(Position::new(15, 0)), Cell::Empty);
+        assert_eq!(schematic.cell(Position::new(0, 2)), Cell::Empty);
+        assert_eq!(
+            schematic.symbols().collect::<Vec<_>>(),
+            vec![(Position::new(14, 0), '*')]
+        );
+    }
 
-        let coordinates: Vec<Position> = number.search_coordinates().collect();
+    #[test]
+    fn test_long_numbers() {
+        assert_eq!(part_one("12345678901234*"), Some(12345678901234));
+        assert_eq!(part_two("2*\n.3"), Some(6));
+    }
 
-        assert_eq!(coordinates, expected_coordinates);
+    #[test]
+    fn test_overflow() {
+        let schematic = "18446744073709551615*1".parse::<Schematic>().unwrap();
+        assert_eq!(sum_of_gear_ratios(&schematic), Ok(u64::MAX));
+        assert_eq!(sum_of_part_numbers(&schematic), Err(Overflow));
+        let schematic = "4294967296*\n4294967296.".parse::<Schematic>().unwrap();
+        assert_eq!(sum_of_gear_ratios(&schematic), Err(Overflow));
     }
 
     #[test]
-    fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(4432));
+    fn test_gears() {
+        assert_eq!(part_two("22.\n.*3\n4.."), Some(0));
+        assert_eq!(part_two("22.\n.*.\n22."), Some(484));
+        assert_eq!(part_two("1.1\n.*.\n..."), Some(1));
     }
 
     #[test]
-    fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(453825));
+    fn test_schematic_parse_error() {
+        let error = "467..114..\n...*..a...".parse::<Schematic>().unwrap_err();
+        assert_eq!((error.line, error.column), (2, 7));
+        assert_eq!(error.expected, "a digit, `.` or a symbol");
+
+        let error = "..99999999999999999999*".parse::<Schematic>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+
+        let error = "...\n..\n...".parse::<Schematic>().unwrap_err();
+        assert_eq!((error.line, error.column), (2, 3));
+        let error = "...\n....".parse::<Schematic>().unwrap_err();
+        assert_eq!(
+            (error.line, error.column, error.found.as_str()),
+            (2, 4, "`.`")
+        );
     }
 }
@@ -1,93 +1,175 @@
-use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::str::FromStr;
+
+use advent_of_code::helper::math::Overflow;
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
+
 advent_of_code::solution!(4);
 
+/// A set of numbers below 128.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct NumberSet(u128);
+
+impl NumberSet {
+    const CAPACITY: usize = 128;
+
+    fn insert(&mut self, number: usize) {
+        self.0 |= 1 << number;
+    }
+
+    fn len(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    fn intersection(self, other: NumberSet) -> NumberSet {
+        NumberSet(self.0 & other.0)
+    }
+
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let mut set = NumberSet::default();
+        parser.many(|parser| {
+            let mut start = *parser;
+            start.skip_spaces();
+            let number = parser.number::<usize>()?;
+            if number >= Self::CAPACITY {
+                return Err(start.error(format!("a number below {}", Self::CAPACITY)));
+            }
+            set.insert(number);
+            Ok(())
+        })?;
+        Ok(set)
+    }
+}
+
+impl FromIterator<usize> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = NumberSet::default();
+        iter.into_iter().for_each(|number| set.insert(number));
+        set
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Card {
     index: usize,
-    winning_numbers: HashSet<usize>,
-    numbers: HashSet<usize>,
+    winning_numbers: NumberSet,
+    numbers: NumberSet,
+    winning_count: usize,
 }
 
 impl Card {
-    fn score(&self) -> usize {
-        let winning_number_count = self.winning_numbers.intersection(&self.numbers).count();
-        match winning_number_count {
-            0 => 0,
-            _ => usize::pow(2, (winning_number_count - 1) as u32),
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let (index, (winning_numbers, numbers)) = parser.key_value(
+            ":",
+            |parser| {
+                parser.word()?;
+                parser.skip_spaces();
+                let id_start = *parser;
+                parser
+                    .number::<usize>()?
+                    .checked_sub(1)
+                    .ok_or_else(|| id_start.error("a card number starting at 1"))
+            },
+            |parser| {
+                let winning_numbers = NumberSet::parse(parser)?;
+                parser.literal("|")?;
+                let numbers = NumberSet::parse(parser)?;
+                Ok((winning_numbers, numbers))
+            },
+        )?;
+        parser.end()?;
+        Ok(Card {
+            index,
+            winning_numbers,
+            numbers,
+            winning_count: winning_numbers.intersection(numbers).len(),
+        })
+    }
+
+    /// 1 point for the first winning number, doubled for each of the others.
+    fn score(&self) -> Result<u64, Overflow> {
+        match self.winning_count {
+            0 => Ok(0),
+            count => u32::try_from(count - 1)
+                .ok()
+                .and_then(|exponent| 2_u64.checked_pow(exponent))
+                .ok_or(Overflow),
         }
     }
 
     fn winning_count(&self) -> usize {
-        self.winning_numbers.intersection(&self.numbers).count()
+        self.winning_count
     }
 }
 
-#[derive(Debug)]
-struct CardParseError;
-
 impl FromStr for Card {
-    type Err = CardParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut winning_numbers_hash = HashSet::with_capacity(10);
-        let mut numbers_hash = HashSet::with_capacity(25);
-        let mut card_index = 0;
-        if let Some((card_id_str, numbers)) = s.split_once(":") {
-            card_index = card_id_str
-                .split_whitespace()
-                .filter(|s| !s.is_empty())
-                .last()
-                .unwrap()
-                .parse::<usize>()
-                .unwrap()
-                - 1;
-            if let Some((winning_numbers, numbers)) = numbers.split_once("|") {
-                for number in winning_numbers.split_whitespace().filter(|s| !s.is_empty()) {
-                    winning_numbers_hash.insert(number.parse::<usize>().unwrap());
-                }
-                for number in numbers.split_whitespace().filter(|s| !s.is_empty()) {
-                    numbers_hash.insert(number.parse::<usize>().unwrap());
-                }
-            }
-        }
-        Ok(Card {
-            index: card_index,
-            winning_numbers: winning_numbers_hash,
-            numbers: numbers_hash,
-        })
+        Card::parse(&mut Parser::new(s))
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| line.parse::<Card>().unwrap())
-            .map(|card| card.score())
-            .sum::<usize>() as u32,
-    )
+pub fn part_one(input: &str) -> Option<u64> {
+    let total = Parser::new(input)
+        .lines()
+        .map(|mut line| Card::parse(&mut line).unwrap())
+        .try_fold(0_u64, |sum, card| {
+            sum.checked_add(card.score()?).ok_or(Overflow)
+        });
+    Some(total.unwrap())
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let cards = input
+/// Parses one card per line, failing at the card number unless cards are numbered in order from 1,
+/// since the copies a card wins are the cards listed after it.
+fn parse_ordered_cards(input: &str) -> impl Iterator<Item = Result<Card, ParseError>> + '_ {
+    Parser::new(input)
         .lines()
-        .map(|line| line.parse::<Card>().unwrap())
-        .collect::<Vec<Card>>();
-    let card_count = cards.len();
-    let mut card_counts_per_id = vec![1u32; card_count];
-    for card in cards.iter() {
-        if card.winning_count() == 0 {
-            continue;
+        .enumerate()
+        .map(|(position, mut line)| {
+            let mut number = line;
+            let card = Card::parse(&mut line)?;
+            if card.index != position {
+                number.word()?;
+                number.skip_spaces();
+                return Err(number.error(format!("card number {}", position + 1)));
+            }
+            Ok(card)
+        })
+}
+
+/// Counts the cards, originals and copies, reading them one at a time. Cards must be in order, as
+/// checked by [`parse_ordered_cards`].
+///
+/// Only the copies won for the next cards are kept, at most one count per winning number. Copies
+/// of cards past the last one are dropped.
+fn total_cards(cards: impl Iterator<Item = Card>) -> Result<u64, Overflow> {
+    let mut won_copies: VecDeque<u64> = VecDeque::new();
+    let mut total = 0_u64;
+    for card in cards {
+        let copies = won_copies
+            .pop_front()
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(Overflow)?;
+        total = total.checked_add(copies).ok_or(Overflow)?;
+        if won_copies.len() < card.winning_count() {
+            won_copies.resize(card.winning_count(), 0);
         }
-        let card_amount = card_counts_per_id[card.index];
-        for additional_card_id in
-            card_counts_per_id[card.index + 1..=card.index + card.winning_count()].iter_mut()
-        {
-            *additional_card_id += card_amount;
+        for count in won_copies.iter_mut().take(card.winning_count()) {
+            *count = count.checked_add(copies).ok_or(Overflow)?;
         }
     }
-    Some(card_counts_per_id.iter().sum())
+    if won_copies.iter().any(|&count| count > 0) {
+        advent_of_code::debug!("dropped copies past the last card: {won_copies:?}");
+    }
+    Ok(total)
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    let cards = parse_ordered_cards(input).map(Result::unwrap);
+    Some(total_cards(cards).unwrap())
 }
 
 #[cfg(test)]
@@ -101,35 +183,60 @@ mod tests {
         assert_eq!(card.index, 0);
         assert_eq!(card.winning_numbers.len(), 10);
         assert_eq!(card.numbers.len(), 10);
-        assert_eq!(card.winning_numbers, HashSet::from_iter(1..=10));
-        assert_eq!(card.numbers, HashSet::from_iter(11..=20));
+        assert_eq!(card.winning_numbers, NumberSet::from_iter(1..=10));
+        assert_eq!(card.numbers, NumberSet::from_iter(11..=20));
+    }
+
+    #[test]
+    fn test_card_parse_error() {
+        let error = "Card 3: 1 2 | 3 x".parse::<Card>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 17));
+        assert_eq!(error.expected, "end of line");
+
+        let error = "Card 0: 1 2 | 3 4".parse::<Card>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 6));
+
+        let error = "Card 1: 1 127 | 128 4".parse::<Card>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 17));
+        assert_eq!(error.expected, "a number below 128");
+    }
+
+    #[test]
+    fn test_card_parse_malformed_number() {
+        // the numbers before `4x` are not returned as a complete card.
+        let error = "Card 1: 41 4x | 83 86".parse::<Card>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 13));
+        assert_eq!(error.found, "`x`");
+
+        let error = "Card 1: 41 | 83 8.6".parse::<Card>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 18));
     }
 
     #[test]
     fn test_card_score() {
         let card = "Game 1: 1 2 3 4 5 6 7 8 9 10 | 11 12 13 14 15 16 17 18 19 20";
         let card = card.parse::<Card>().unwrap();
-        assert_eq!(card.score(), 0);
+        assert_eq!(card.score(), Ok(0));
         assert_eq!(card.winning_count(), 0);
 
         let card = "Game 1: 1 2 3 4 5 6 7 8 9 10 | 1 12 13 14 15 16 17 18 19 20";
         let card = card.parse::<Card>().unwrap();
-        assert_eq!(card.score(), 1);
+        assert_eq!(card.score(), Ok(1));
         assert_eq!(card.winning_count(), 1);
 
         let card = "Game 1: 1 2 3 4 5 6 7 8 9 10 | 1 2 13 14 15 16 17 18 19 20";
         let card = card.parse::<Card>().unwrap();
-        assert_eq!(card.score(), 2);
+        assert_eq!(card.score(), Ok(2));
         assert_eq!(card.winning_count(), 2);
 
         let card = "Game 1: 1 2 3 4 5 6 7 8 9 10 | 1 2 3 14 15 16 17 18 19 20";
         let card = card.parse::<Card>().unwrap();
-        assert_eq!(card.score(), 4);
+        assert_eq!(card.score(), Ok(4));
         assert_eq!(card.winning_count(), 3);
 
         let card = "Game 1: 1 2 3 4 5 6 7 8 9 10 | 1 2 3 4 15 16 17 18 19 20";
         let card = card.parse::<Card>().unwrap();
-        assert_eq!(card.score(), 8);
+        assert_eq!(card.score(), Ok(8));
         assert_eq!(card.winning_count(), 4);
     }
 
@@ -144,4 +251,49 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(30));
     }
+
+    #[test]
+    fn test_overflow() {
+        let numbers = (0..64).map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let card = format!("Card 1: {numbers} | {numbers}");
+        assert_eq!(card.parse::<Card>().unwrap().score(), Ok(1 << 63));
+        let numbers = (0..65).map(|n| n.to_string()).collect::<Vec<_>>().join(" ");
+        let card = format!("Card 1: {numbers} | {numbers}");
+        assert_eq!(card.parse::<Card>().unwrap().score(), Err(Overflow));
+
+        // each card wins one copy of every following card, doubling the copies each time.
+        let cards = (1..=70)
+            .map(|index| format!("Card {index}: {numbers} | {numbers}"))
+            .collect::<Vec<_>>();
+        let total = |count: usize| {
+            total_cards(
+                cards[..count]
+                    .iter()
+                    .map(|card| card.parse::<Card>().unwrap()),
+            )
+        };
+        assert_eq!(total(63), Ok(u64::MAX >> 1));
+        assert_eq!(total(70), Err(Overflow));
+    }
+
+    #[test]
+    fn test_copies_past_last_card() {
+        let input = "Card 1: 1 2 | 1 2\nCard 2: 1 2 3 | 1 2 3";
+        assert_eq!(part_two(input), Some(1 + 2));
+    }
+
+    #[test]
+    fn test_cards_out_of_order() {
+        let first_error = |input| parse_ordered_cards(input).find_map(Result::err).unwrap();
+        let error = first_error("Card 1: 1 | 1\nCard   3: 2 | 2\nCard 2: 3 | 3");
+        assert_eq!((error.line, error.column), (2, 8));
+        assert_eq!(
+            (error.expected.as_str(), error.found.as_str()),
+            ("card number 2", "`3:`")
+        );
+
+        let error = first_error("Card 2: 1 | 1");
+        assert_eq!((error.line, error.column), (1, 6));
+        assert!(parse_ordered_cards("Card 1: 1 | 1\nCard 2: 1 | 1").all(|card| card.is_ok()));
+    }
 }
@@ -1,10 +1,10 @@
-use itertools::Itertools;
-use std::cmp::min;
+use std::collections::{HashMap, VecDeque};
 use std::str::FromStr;
-use std::sync::mpsc::channel;
-use std::sync::Arc;
-use threadpool::ThreadPool;
-advent_of_code::solution!(5);
+
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
+
+advent_of_code::solution!(5, part_two => [map_by_map]);
 
 #[derive(Debug, PartialEq)]
 struct Range {
@@ -13,36 +13,85 @@ struct Range {
     length: u64,
 }
 
-#[derive(Debug)]
-struct RangeParseError;
-
 impl FromStr for Range {
-    type Err = RangeParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut numbers_iter = s
-            .split_whitespace()
-            .filter(|s| !s.is_empty())
-            .map(|s| s.parse::<u64>().unwrap());
-        let destination_start = numbers_iter.next().unwrap();
-        let source_start = numbers_iter.next().unwrap();
-        let length = numbers_iter.next().unwrap();
+        Range::parse(&mut Parser::new(s))
+    }
+}
+
+/// Returns the last value of the range starting at `start`, failing if it doesn't fit in a [`u64`].
+fn checked_last(start: u64, length: u64, length_parser: &Parser) -> Result<u64, ParseError> {
+    start
+        .checked_add(length.saturating_sub(1))
+        .ok_or_else(|| length_parser.error("a length that keeps the range within 64 bits"))
+}
+
+impl Range {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let destination_start = parser.number::<u64>()?;
+        let source_start = parser.number::<u64>()?;
+        parser.skip_spaces();
+        let length_parser = *parser;
+        let length = parser.number::<u64>()?;
+        checked_last(destination_start, length, &length_parser)?;
+        checked_last(source_start, length, &length_parser)?;
+        parser.end()?;
         Ok(Range {
             source_start,
             destination_start,
             length,
         })
     }
-}
 
-impl Range {
+    /// The last source value of the range, which must not be empty.
+    fn source_last(&self) -> u64 {
+        self.source_start + (self.length - 1)
+    }
+
     fn mapped_value(&self, value: u64) -> Option<u64> {
-        if value >= self.source_start && value < self.source_start + self.length {
+        if value >= self.source_start && value - self.source_start < self.length {
             Some(self.destination_start + (value - self.source_start))
         } else {
             None
         }
     }
+
+    /// Splits `interval` into the part covered by this range, mapped to its destination,
+    /// and the parts before and after the range, which are left untouched.
+    fn mapped_interval(&self, interval: Interval) -> (Option<Interval>, [Option<Interval>; 2]) {
+        if self.length == 0 {
+            return (None, [Some(interval), None]);
+        }
+        let source_last = self.source_start + (self.length - 1);
+        if interval.last < self.source_start || interval.first > source_last {
+            return (None, [Some(interval), None]);
+        }
+
+        let first = interval.first.max(self.source_start);
+        let last = interval.last.min(source_last);
+        let inside = Interval {
+            first: self.destination_start + (first - self.source_start),
+            last: self.destination_start + (last - self.source_start),
+        };
+        let before = (interval.first < self.source_start).then(|| Interval {
+            first: interval.first,
+            last: self.source_start - 1,
+        });
+        let after = (interval.last > source_last).then(|| Interval {
+            first: source_last + 1,
+            last: interval.last,
+        });
+        (Some(inside), [before, after])
+    }
+}
+
+/// An inclusive interval of values, so that intervals ending at [`u64::MAX`] can be represented.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct Interval {
+    first: u64,
+    last: u64,
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -51,64 +100,321 @@ struct SeedRange {
     length: u64,
 }
 
+impl SeedRange {
+    fn parse(parser: &mut Parser) -> Result<Self, ParseError> {
+        let source_start = parser.number::<u64>()?;
+        parser.skip_spaces();
+        let length_parser = *parser;
+        let length = parser.number::<u64>()?;
+        checked_last(source_start, length, &length_parser)?;
+        Ok(SeedRange {
+            source_start,
+            length,
+        })
+    }
+
+    #[cfg(test)]
+    fn contains(&self, seed: u64) -> bool {
+        seed >= self.source_start && seed - self.source_start < self.length
+    }
+
+    fn interval(&self) -> Option<Interval> {
+        (self.length > 0).then(|| Interval {
+            first: self.source_start,
+            last: self.source_start + (self.length - 1),
+        })
+    }
+}
+
+/// Two ranges of a map sending the same source value, by their indices in the unsorted ranges.
+#[derive(Debug, PartialEq)]
+struct OverlappingRanges(usize, usize);
+
+/// A map from a source category to a destination category.
 #[derive(Debug, PartialEq)]
 struct Map {
+    source: String,
+    destination: String,
+    /// Non-empty and disjoint, sorted by source.
     ranges: Vec<Range>,
 }
 
 impl Map {
+    /// Sorts the ranges and drops the empty ones, failing if two ranges overlap.
+    fn new(source: &str, destination: &str, ranges: Vec<Range>) -> Result<Self, OverlappingRanges> {
+        let mut ranges = ranges
+            .into_iter()
+            .enumerate()
+            .filter(|(_, range)| range.length > 0)
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|(_, range)| range.source_start);
+        for pair in ranges.windows(2) {
+            let ((first_index, first), (second_index, second)) = (&pair[0], &pair[1]);
+            if first.source_last() >= second.source_start {
+                let (first_index, second_index) = (*first_index, *second_index);
+                return Err(OverlappingRanges(
+                    first_index.min(second_index),
+                    first_index.max(second_index),
+                ));
+            }
+        }
+        Ok(Map {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            ranges: ranges.into_iter().map(|(_, range)| range).collect(),
+        })
+    }
+
+    /// The map sending every value of `category` to itself.
+    fn identity(category: &str) -> Self {
+        Map {
+            source: category.to_string(),
+            destination: category.to_string(),
+            ranges: Vec::new(),
+        }
+    }
+
     fn mapped_value(&self, value: u64) -> u64 {
-        for range in &self.ranges {
-            if let Some(mapped_value) = range.mapped_value(value) {
-                return mapped_value;
+        // the only range which can contain the value is the last one starting at or before it.
+        let index = self
+            .ranges
+            .partition_point(|range| range.source_start <= value);
+        index
+            .checked_sub(1)
+            .and_then(|index| self.ranges[index].mapped_value(value))
+            .unwrap_or(value)
+    }
+
+    /// The intervals of values outside every range, which the map leaves unchanged, in order.
+    fn gaps(&self) -> impl Iterator<Item = Interval> + '_ {
+        // the first value after the previous range, `None` once past `u64::MAX`.
+        let mut first = Some(0);
+        self.ranges
+            .iter()
+            .map(Some)
+            .chain([None])
+            .filter_map(move |range| {
+                let gap_first = first?;
+                let Some(range) = range else {
+                    first = None;
+                    return Some(Interval {
+                        first: gap_first,
+                        last: u64::MAX,
+                    });
+                };
+                first = range.source_last().checked_add(1);
+                (gap_first < range.source_start).then(|| Interval {
+                    first: gap_first,
+                    last: range.source_start - 1,
+                })
+            })
+    }
+
+    /// Maps every value of `intervals`, splitting them at the boundaries of the ranges.
+    fn mapped_intervals(&self, intervals: Vec<Interval>) -> Vec<Interval> {
+        self.mapped_pieces(intervals)
+            .into_iter()
+            .map(|(_, image)| image)
+            .collect()
+    }
+
+    /// Same as [`Map::mapped_intervals`], keeping each piece of the source intervals along with
+    /// its image.
+    fn mapped_pieces(&self, intervals: Vec<Interval>) -> Vec<(Interval, Interval)> {
+        let mut mapped = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            // the ranges are sorted and disjoint, so the ones overlapping the interval follow the
+            // first range that doesn't end before it.
+            let first_range = self
+                .ranges
+                .partition_point(|range| range.source_last() < interval.first);
+            let mut rest = Some(interval);
+            for range in &self.ranges[first_range..] {
+                let Some(interval) = rest.filter(|rest| range.source_start <= rest.last) else {
+                    break;
+                };
+                let (inside, [before, after]) = range.mapped_interval(interval);
+                mapped.extend(before.map(|before| (before, before)));
+                mapped.extend(inside.map(|image| {
+                    let source = Interval {
+                        first: image.first - range.destination_start + range.source_start,
+                        last: image.last - range.destination_start + range.source_start,
+                    };
+                    (source, image)
+                }));
+                rest = after;
             }
+            mapped.extend(rest.map(|rest| (rest, rest)));
         }
-        value
+        mapped
+    }
+
+    /// The map applying this map, then `next`, as a single map.
+    fn compose(&self, next: &Map) -> Map {
+        let pieces = self
+            .ranges
+            .iter()
+            .map(|range| {
+                let source = Interval {
+                    first: range.source_start,
+                    last: range.source_last(),
+                };
+                let image = Interval {
+                    first: range.destination_start,
+                    last: range.destination_start + (range.length - 1),
+                };
+                (source, image)
+            })
+            .chain(self.gaps().map(|gap| (gap, gap)));
+        let mut ranges = Vec::new();
+        for (source, middle) in pieces {
+            for (middle_part, image) in next.mapped_pieces(vec![middle]) {
+                let first = source.first + (middle_part.first - middle.first);
+                // values mapped to themselves don't need a range.
+                if image.first != first {
+                    ranges.push(Range {
+                        source_start: first,
+                        destination_start: image.first,
+                        length: image.last - image.first + 1,
+                    });
+                }
+            }
+        }
+        // the pieces of `self` are disjoint, and so are their parts.
+        ranges.sort_by_key(|range| range.source_start);
+        Map {
+            source: self.source.clone(),
+            destination: next.destination.clone(),
+            ranges,
+        }
+    }
+
+    /// Parses a `<source>-to-<destination> map:` header followed by one range per line.
+    fn parse(section: &Parser) -> Result<Self, ParseError> {
+        let mut lines = section.lines();
+        let mut header = lines.next().unwrap_or(*section);
+        let source = header.word()?.to_string();
+        header.literal("-to-")?;
+        let destination = header.word()?.to_string();
+        header.literal("map:")?;
+        header.end()?;
+        let lines = lines.collect::<Vec<_>>();
+        let ranges = lines
+            .iter()
+            .copied()
+            .map(|mut line| Range::parse(&mut line))
+            .collect::<Result<_, _>>()?;
+        Map::new(&source, &destination, ranges).map_err(|OverlappingRanges(first, second)| {
+            let expected = format!("a range not overlapping line {}", lines[first].line());
+            lines[second].error(&expected)
+        })
     }
 }
 
+/// The inverse of a map, from destination values back to the sets of source values mapped to them.
+/// A destination can have no source, or several sources when ranges send values onto each other.
+#[cfg(test)]
+struct InverseMap {
+    /// The images of the pieces of the map, with the pieces they come from.
+    pieces: Vec<(Interval, Interval)>,
+}
+
+#[cfg(test)]
+impl InverseMap {
+    fn new(map: &Map) -> Self {
+        let everything = Interval {
+            first: 0,
+            last: u64::MAX,
+        };
+        let pieces = map
+            .mapped_pieces(vec![everything])
+            .into_iter()
+            .map(|(source, image)| (image, source))
+            .collect();
+        InverseMap { pieces }
+    }
+
+    /// The source values mapped to `value`.
+    fn sources(&self, value: u64) -> impl Iterator<Item = u64> + '_ {
+        self.pieces
+            .iter()
+            .filter(move |(image, _)| image.first <= value && value <= image.last)
+            .map(move |(image, source)| source.first + (value - image.first))
+    }
+}
+
+/// Finds the maps leading from the `source` category to the `target` category, through as few
+/// categories as possible. The maps can be listed in any order.
+fn map_path<'a>(maps: &'a [Map], source: &str, target: &str) -> Option<Vec<&'a Map>> {
+    // the map used to reach each category.
+    let mut reached_by: HashMap<&str, Option<&Map>> = HashMap::from([(source, None)]);
+    let mut queue = VecDeque::from([source]);
+    while let Some(category) = queue.pop_front() {
+        if category == target {
+            let mut path = Vec::new();
+            let mut current = target;
+            while let Some(map) = reached_by[current] {
+                path.push(map);
+                current = &map.source;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for map in maps.iter().filter(|map| map.source == category) {
+            if !reached_by.contains_key(map.destination.as_str()) {
+                reached_by.insert(&map.destination, Some(map));
+                queue.push_back(&map.destination);
+            }
+        }
+    }
+    None
+}
+
+/// Composes the maps from `source` to `target` into a single map.
+fn composed_map(maps: &[Map], source: &str, target: &str) -> Option<Map> {
+    let path = map_path(maps, source, target)?;
+    Some(
+        path.iter()
+            .fold(Map::identity(source), |composed, map| composed.compose(map)),
+    )
+}
+
 #[derive(Debug, PartialEq)]
 struct Almanac {
     seeds: Vec<u64>,
     maps: Vec<Map>,
 }
 
-#[derive(Debug)]
-struct AlmanacParseError;
+/// Parses the `seeds:` line with `seed` and the maps that follow it.
+fn parse_almanac<T>(
+    s: &str,
+    seed: impl FnMut(&mut Parser) -> Result<T, ParseError>,
+) -> Result<(Vec<T>, Vec<Map>), ParseError> {
+    let sections = Parser::new(s).sections();
+    let Some((seeds_section, map_sections)) = sections.split_first() else {
+        return Err(Parser::new(s).error("`seeds:`"));
+    };
+
+    let mut seeds_section = *seeds_section;
+    let (_, seeds) = seeds_section.key_value(
+        ":",
+        |parser| parser.literal("seeds"),
+        |parser| parser.many(seed),
+    )?;
+    seeds_section.end()?;
+
+    let maps = map_sections
+        .iter()
+        .map(Map::parse)
+        .collect::<Result<_, _>>()?;
+    Ok((seeds, maps))
+}
 
 impl FromStr for Almanac {
-    type Err = AlmanacParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut seeds = Vec::new();
-        let mut maps = Vec::new();
-
-        let mut all_lines = s.lines();
-
-        // seeds
-        let mut lines = all_lines.by_ref().take_while(|line| !line.is_empty());
-        for seed in lines
-            .next()
-            .unwrap()
-            .split(":")
-            .nth(1)
-            .unwrap()
-            .split_whitespace()
-            .filter(|s| !s.is_empty())
-        {
-            seeds.push(seed.parse::<u64>().unwrap());
-        }
-        all_lines.next();
-
-        for _ in 0..7 {
-            let mut ranges = Vec::new();
-            let mut lines = all_lines.by_ref().take_while(|line| !line.is_empty());
-            lines.next();
-            for range in lines.filter(|s| !s.is_empty()) {
-                ranges.push(range.parse::<Range>().unwrap());
-            }
-            maps.push(Map { ranges });
-        }
+        let (seeds, maps) = parse_almanac(s, |parser| parser.number::<u64>())?;
         Ok(Almanac { seeds, maps })
     }
 }
@@ -119,118 +425,101 @@ struct AlmanacSeedRange {
     maps: Vec<Map>,
 }
 
-#[derive(Debug)]
-struct AlmanacSeedRangeParseError;
-
 impl FromStr for AlmanacSeedRange {
-    type Err = AlmanacSeedRangeParseError;
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut seed_ranges = Vec::new();
-        let mut maps = Vec::new();
-
-        let mut all_lines = s.lines();
-
-        // seeds
-        let mut lines = all_lines.by_ref().take_while(|line| !line.is_empty());
-        let seeds = lines.next().unwrap().split(":").nth(1).unwrap();
-        // take 2 item from iterator until iterator is empty
-        for mut chunk in &seeds.split_whitespace().filter(|s| !s.is_empty()).chunks(2) {
-            let source_start = chunk.next().unwrap().parse::<u64>().unwrap();
-            let length = chunk.next().unwrap().parse::<u64>().unwrap();
-            seed_ranges.push(SeedRange {
-                source_start,
-                length,
-            });
-        }
-        all_lines.next();
-
-        for _ in 0..7 {
-            let mut ranges = Vec::new();
-            let mut lines = all_lines.by_ref().take_while(|line| !line.is_empty());
-            lines.next();
-            for range in lines.filter(|s| !s.is_empty()) {
-                ranges.push(range.parse::<Range>().unwrap());
-            }
-            maps.push(Map { ranges });
-        }
+        let (seed_ranges, maps) = parse_almanac(s, SeedRange::parse)?;
         Ok(AlmanacSeedRange { seed_ranges, maps })
     }
 }
 
 impl AlmanacSeedRange {
-    fn calculate_for_seed(&self, seed: u64) -> u64 {
-        self.maps
+    /// Pushes the seed intervals through the seed to location map, then takes the lowest location.
+    fn lowest_location(&self) -> Option<u64> {
+        let seeds = self
+            .seed_ranges
             .iter()
-            .fold(seed, |value, map| map.mapped_value(value))
+            .filter_map(SeedRange::interval)
+            .collect();
+        composed_map(&self.maps, "seed", "location")?
+            .mapped_intervals(seeds)
+            .iter()
+            .map(|interval| interval.first)
+            .min()
     }
-    fn calculate_for_seed_range(&self, start: u64, length: u64) -> u64 {
-        if length == 1 {
-            return min(
-                self.calculate_for_seed(start),
-                self.calculate_for_seed(start + 1),
-            );
-        }
-        let step = length / 2;
 
-        let start_value = self.calculate_for_seed(start);
-        let middle_value = self.calculate_for_seed(start + step);
-        let end_value = self.calculate_for_seed(start + length);
+    /// Same as [`AlmanacSeedRange::lowest_location`], pushing the seed intervals through each map
+    /// in turn instead of composing the maps first.
+    fn lowest_location_map_by_map(&self) -> Option<u64> {
+        let seeds = self
+            .seed_ranges
+            .iter()
+            .filter_map(SeedRange::interval)
+            .collect();
+        map_path(&self.maps, "seed", "location")?
+            .iter()
+            .fold(seeds, |intervals, map| map.mapped_intervals(intervals))
+            .iter()
+            .map(|interval| interval.first)
+            .min()
+    }
 
-        let mut min_value = u64::MAX;
-        if start_value + step != middle_value {
-            min_value = self.calculate_for_seed_range(start, step);
+    /// Walks the locations up from 0 and returns the first one that some seed reaches, by
+    /// following the inverse maps back to the seeds. Takes as many steps as the lowest location.
+    #[cfg(test)]
+    fn lowest_location_reverse(&self) -> Option<u64> {
+        if self.seed_ranges.iter().all(|range| range.length == 0) {
+            return None;
         }
-        if middle_value + (length - step) != end_value {
-            min_value = min(
-                min_value,
-                self.calculate_for_seed_range(start + step, length - step),
-            );
-        }
-        min_value
+        let inverses = map_path(&self.maps, "seed", "location")?
+            .into_iter()
+            .rev()
+            .map(InverseMap::new)
+            .collect::<Vec<_>>();
+        (0..=u64::MAX).find(|&location| {
+            let seeds = inverses.iter().fold(vec![location], |values, inverse| {
+                values
+                    .iter()
+                    .flat_map(|&value| inverse.sources(value))
+                    .collect()
+            });
+            seeds
+                .into_iter()
+                .any(|seed| self.seed_ranges.iter().any(|range| range.contains(seed)))
+        })
     }
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
     let almanac = input.parse::<Almanac>().unwrap();
+    // a few lookups per seed are cheaper than composing the maps first.
+    let path = map_path(&almanac.maps, "seed", "location")?;
     almanac
         .seeds
         .iter()
-        .map(|seed| {
-            almanac
-                .maps
-                .iter()
-                .fold(*seed, |value, map| map.mapped_value(value))
-        })
+        .map(|&seed| path.iter().fold(seed, |value, map| map.mapped_value(value)))
         .min()
-        .into()
 }
 
 pub fn part_two(input: &str) -> Option<u64> {
-    let num_threads = 4;
-    let almanac = input.parse::<AlmanacSeedRange>().unwrap();
-    let pool = ThreadPool::new(num_threads);
-    let almanac = Arc::new(almanac);
-
-    let (tx, rx) = channel();
-    let mut work_count = 0;
-    for seed_range in almanac.seed_ranges.clone() {
-        work_count += 1;
-        let tx = tx.clone();
-        let arc_almanac = almanac.clone();
-        let seed_range = seed_range.clone();
-        pool.execute(move || {
-            let result =
-                arc_almanac.calculate_for_seed_range(seed_range.source_start, seed_range.length);
-            tx.send(result).unwrap();
-        });
-    }
+    input.parse::<AlmanacSeedRange>().unwrap().lowest_location()
+}
 
-    Some(rx.iter().take(work_count).min().unwrap())
+pub fn map_by_map(input: &str) -> Option<u64> {
+    input
+        .parse::<AlmanacSeedRange>()
+        .unwrap()
+        .lowest_location_map_by_map()
 }
 
 #[cfg(test)]
 mod tests {
+    use std::ops::RangeInclusive;
+
+    use advent_of_code::helper::property::{Property, Source};
+    use advent_of_code::template::input_gen::generate;
+
     use super::*;
 
     #[test]
@@ -270,7 +559,11 @@ mod tests {
                 length: 30,
             },
         ];
-        let map = Map { ranges };
+        let map = Map {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            ranges,
+        };
 
         assert_eq!(map.mapped_value(25), 15);
         assert_eq!(map.mapped_value(65), 55);
@@ -307,20 +600,24 @@ humidity-to-location map:
             seeds: vec![79, 14, 55, 13],
             maps: vec![
                 Map {
+                    source: "seed".to_string(),
+                    destination: "soil".to_string(),
                     ranges: vec![
-                        Range {
-                            source_start: 98,
-                            destination_start: 50,
-                            length: 2,
-                        },
                         Range {
                             source_start: 50,
                             destination_start: 52,
                             length: 48,
                         },
+                        Range {
+                            source_start: 98,
+                            destination_start: 50,
+                            length: 2,
+                        },
                     ],
                 },
                 Map {
+                    source: "soil".to_string(),
+                    destination: "fertilizer".to_string(),
                     ranges: vec![Range {
                         source_start: 15,
                         destination_start: 0,
@@ -328,6 +625,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "fertilizer".to_string(),
+                    destination: "water".to_string(),
                     ranges: vec![Range {
                         source_start: 53,
                         destination_start: 49,
@@ -335,6 +634,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "water".to_string(),
+                    destination: "light".to_string(),
                     ranges: vec![Range {
                         source_start: 18,
                         destination_start: 88,
@@ -342,6 +643,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "light".to_string(),
+                    destination: "temperature".to_string(),
                     ranges: vec![Range {
                         source_start: 77,
                         destination_start: 45,
@@ -349,6 +652,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "temperature".to_string(),
+                    destination: "humidity".to_string(),
                     ranges: vec![Range {
                         source_start: 69,
                         destination_start: 0,
@@ -356,6 +661,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "humidity".to_string(),
+                    destination: "location".to_string(),
                     ranges: vec![Range {
                         source_start: 56,
                         destination_start: 60,
@@ -409,20 +716,24 @@ humidity-to-location map:
             ],
             maps: vec![
                 Map {
+                    source: "seed".to_string(),
+                    destination: "soil".to_string(),
                     ranges: vec![
-                        Range {
-                            source_start: 98,
-                            destination_start: 50,
-                            length: 2,
-                        },
                         Range {
                             source_start: 50,
                             destination_start: 52,
                             length: 48,
                         },
+                        Range {
+                            source_start: 98,
+                            destination_start: 50,
+                            length: 2,
+                        },
                     ],
                 },
                 Map {
+                    source: "soil".to_string(),
+                    destination: "fertilizer".to_string(),
                     ranges: vec![Range {
                         source_start: 15,
                         destination_start: 0,
@@ -430,6 +741,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "fertilizer".to_string(),
+                    destination: "water".to_string(),
                     ranges: vec![Range {
                         source_start: 53,
                         destination_start: 49,
@@ -437,6 +750,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "water".to_string(),
+                    destination: "light".to_string(),
                     ranges: vec![Range {
                         source_start: 18,
                         destination_start: 88,
@@ -444,6 +759,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "light".to_string(),
+                    destination: "temperature".to_string(),
                     ranges: vec![Range {
                         source_start: 77,
                         destination_start: 45,
@@ -451,6 +768,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "temperature".to_string(),
+                    destination: "humidity".to_string(),
                     ranges: vec![Range {
                         source_start: 69,
                         destination_start: 0,
@@ -458,6 +777,8 @@ humidity-to-location map:
                     }],
                 },
                 Map {
+                    source: "humidity".to_string(),
+                    destination: "location".to_string(),
                     ranges: vec![Range {
                         source_start: 56,
                         destination_start: 60,
@@ -472,6 +793,431 @@ humidity-to-location map:
         assert_eq!(result.unwrap(), expected);
     }
 
+    #[test]
+    fn test_almanac_parse_error() {
+        let input = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50
+
+soil-to-fertilizer map:
+0 15 37";
+        let error = input.parse::<Almanac>().unwrap_err();
+        assert_eq!((error.line, error.column), (5, 6));
+        assert_eq!(error.expected, "a number");
+
+        let error = "seeds: 79 14 55".parse::<AlmanacSeedRange>().unwrap_err();
+        assert_eq!((error.line, error.column), (1, 16));
+        assert_eq!(error.expected, "a number");
+    }
+
+    /// Lowest location by mapping every single seed.
+    fn brute_force_lowest_location(input: &str) -> Option<u64> {
+        let almanac = input.parse::<AlmanacSeedRange>().unwrap();
+        almanac
+            .seed_ranges
+            .iter()
+            .flat_map(|range| range.source_start..range.source_start + range.length)
+            .map(|seed| {
+                map_path(&almanac.maps, "seed", "location")
+                    .unwrap()
+                    .iter()
+                    .fold(seed, |value, map| map.mapped_value(value))
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_range_mapped_interval() {
+        let range = Range {
+            source_start: 20,
+            destination_start: 100,
+            length: 10,
+        };
+        let interval = |first, last| Interval { first, last };
+
+        assert_eq!(
+            range.mapped_interval(interval(15, 35)),
+            (
+                Some(interval(100, 109)),
+                [Some(interval(15, 19)), Some(interval(30, 35))]
+            )
+        );
+        assert_eq!(
+            range.mapped_interval(interval(22, 24)),
+            (Some(interval(102, 104)), [None, None])
+        );
+        assert_eq!(
+            range.mapped_interval(interval(30, 40)),
+            (None, [Some(interval(30, 40)), None])
+        );
+    }
+
+    #[test]
+    fn test_map_rejects_overlapping_ranges() {
+        let range = |source_start, length| Range {
+            source_start,
+            destination_start: 1000,
+            length,
+        };
+        assert_eq!(
+            Map::new(
+                "seed",
+                "soil",
+                vec![range(15, 10), range(30, 1), range(10, 10)]
+            ),
+            Err(OverlappingRanges(0, 2))
+        );
+        assert_eq!(
+            Map::new("seed", "soil", vec![range(10, 5), range(14, 1)]),
+            Err(OverlappingRanges(0, 1))
+        );
+        // ranges can touch, and empty ranges are dropped.
+        let map = Map::new(
+            "seed",
+            "soil",
+            vec![range(15, 5), range(12, 0), range(10, 5)],
+        )
+        .unwrap();
+        assert_eq!(map.ranges, vec![range(10, 5), range(15, 5)]);
+
+        let input = "seeds: 1
+
+seed-to-soil map:
+50 98 2
+52 50 48
+0 90 10";
+        let error = input.parse::<Almanac>().unwrap_err();
+        assert_eq!((error.line, error.column), (6, 1));
+        assert_eq!(error.expected, "a range not overlapping line 5");
+    }
+
+    #[test]
+    fn test_map_lookup_and_gaps() {
+        let map = Map::new(
+            "seed",
+            "soil",
+            vec![
+                Range {
+                    source_start: 20,
+                    destination_start: 0,
+                    length: 5,
+                },
+                Range {
+                    source_start: 10,
+                    destination_start: 100,
+                    length: 10,
+                },
+                Range {
+                    source_start: u64::MAX - 1,
+                    destination_start: 7,
+                    length: 2,
+                },
+            ],
+        )
+        .unwrap();
+        let interval = |first, last| Interval { first, last };
+        assert_eq!(
+            map.gaps().collect::<Vec<_>>(),
+            vec![interval(0, 9), interval(25, u64::MAX - 2)]
+        );
+        assert_eq!(
+            Map::identity("seed").gaps().collect::<Vec<_>>(),
+            vec![interval(0, u64::MAX)]
+        );
+
+        for value in (0..40).chain([u64::MAX - 2, u64::MAX - 1, u64::MAX]) {
+            let expected = map
+                .ranges
+                .iter()
+                .find_map(|range| range.mapped_value(value))
+                .unwrap_or(value);
+            assert_eq!(map.mapped_value(value), expected, "{value}");
+            let in_gap = map
+                .gaps()
+                .any(|gap| gap.first <= value && value <= gap.last);
+            assert_eq!(
+                in_gap,
+                map.ranges
+                    .iter()
+                    .all(|range| range.mapped_value(value).is_none())
+            );
+        }
+
+        let mut values: Vec<u64> = map
+            .mapped_intervals(vec![interval(0, 30)])
+            .iter()
+            .flat_map(|interval| interval.first..=interval.last)
+            .collect();
+        values.sort();
+        let mut expected: Vec<u64> = (0..=30).map(|value| map.mapped_value(value)).collect();
+        expected.sort();
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn test_mapped_pieces_scale() {
+        // 100000 ranges and intervals: splitting them against every range would take 10^10 steps.
+        let count = 100_000;
+        let ranges = (0..count)
+            .map(|i| Range {
+                source_start: 10 * i,
+                destination_start: 10 * (count - i),
+                length: 5,
+            })
+            .collect();
+        let map = Map::new("seed", "soil", ranges).unwrap();
+        let intervals = (0..count)
+            .map(|i| Interval {
+                first: 10 * i + 2,
+                last: 10 * i + 7,
+            })
+            .collect();
+        let pieces = map.mapped_pieces(intervals);
+        // each interval is split into a mapped part and a part in the following gap.
+        assert_eq!(pieces.len(), 2 * count as usize);
+        let interval = |first, last| Interval { first, last };
+        assert_eq!(pieces[2], (interval(12, 14), interval(999_992, 999_994)));
+        assert_eq!(pieces[3], (interval(15, 17), interval(15, 17)));
+    }
+
+    #[test]
+    fn test_part_two_minimum_hidden_inside_linear_range() {
+        // seeds 10..110 map linearly at their start, middle and end,
+        // but seeds 30..35 are sent to location 0.
+        let input = "seeds: 10 100
+
+seed-to-location map:
+0 30 5";
+        assert_eq!(part_two(input), Some(0));
+        assert_eq!(part_two(input), brute_force_lowest_location(input));
+    }
+
+    #[test]
+    fn test_part_two_last_seed_of_range() {
+        // only the very last seed of the range is mapped lower.
+        let input = "seeds: 50 10
+
+seed-to-soil map:
+1 59 1
+
+soil-to-location map:
+0 1 1";
+        assert_eq!(part_two(input), Some(0));
+    }
+
+    #[test]
+    fn test_part_two_ranges_ending_at_u64_max() {
+        let input = "seeds: 18446744073709551610 6
+
+seed-to-location map:
+0 18446744073709551612 4
+18446744073709551612 0 4";
+        assert_eq!(part_two(input), Some(0));
+
+        let error = "seeds: 18446744073709551610 7"
+            .parse::<AlmanacSeedRange>()
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (1, 29));
+    }
+
+    /// A random almanac with small numbers and empty ranges, going from seeds to locations
+    /// through `maps` maps. Ranges overlapping previous ones in a map are skipped.
+    fn random_almanac(
+        source: &mut Source,
+        seed_ranges: RangeInclusive<usize>,
+        maps: RangeInclusive<usize>,
+    ) -> String {
+        let mut input = String::from("seeds:");
+        for (start, length) in source.vec(seed_ranges, |s| (s.below(100), s.below(40))) {
+            input.push_str(&format!(" {start} {length}"));
+        }
+        let maps = source.range(*maps.start() as u64..=*maps.end() as u64);
+        let category = |index| match index {
+            0 => "seed".to_string(),
+            _ if index == maps => "location".to_string(),
+            _ => format!("category{index}"),
+        };
+        for map in 0..maps {
+            input.push_str(&format!(
+                "\n\n{}-to-{} map:",
+                category(map),
+                category(map + 1)
+            ));
+            let ranges = source.vec(0..=4, |s| (s.below(150), s.below(150), s.below(30)));
+            let mut sources: Vec<(u64, u64)> = Vec::new();
+            for (destination, start, length) in ranges {
+                let end = start + length;
+                if length > 0 && sources.iter().any(|&(s, e)| start < e && s < end) {
+                    continue;
+                }
+                sources.push((start, end));
+                input.push_str(&format!("\n{destination} {start} {length}"));
+            }
+        }
+        input
+    }
+
+    #[test]
+    fn test_part_two_matches_brute_force() {
+        Property::new().check(
+            |source| random_almanac(source, 1..=3, 1..=7),
+            |input| part_two(input),
+            |input| brute_force_lowest_location(input),
+        );
+        Property::new().check(
+            |source| random_almanac(source, 1..=3, 1..=7),
+            |input| map_by_map(input),
+            |input| brute_force_lowest_location(input),
+        );
+    }
+
+    #[test]
+    fn test_composed_map_matches_maps() {
+        let almanac = advent_of_code::template::read_file("examples", DAY)
+            .parse::<Almanac>()
+            .unwrap();
+        // follows the maps of the example one by one, as they form a single chain.
+        let lookup = |source: &str, target: &str, mut value| {
+            let mut category = source;
+            while category != target {
+                let map = almanac.maps.iter().find(|map| map.source == category)?;
+                value = map.mapped_value(value);
+                category = &map.destination;
+            }
+            Some(value)
+        };
+        for (source, target, length) in [
+            ("seed", "location", 7),
+            ("humidity", "location", 1),
+            ("seed", "water", 3),
+            ("soil", "humidity", 5),
+        ] {
+            let composed = composed_map(&almanac.maps, source, target).unwrap();
+            let path = map_path(&almanac.maps, source, target).unwrap();
+            assert_eq!(path.len(), length, "{source} to {target}");
+            for value in 0..200 {
+                assert_eq!(
+                    Some(composed.mapped_value(value)),
+                    lookup(source, target, value),
+                    "{source} {value} to {target}"
+                );
+            }
+            assert!(composed
+                .ranges
+                .windows(2)
+                .all(|pair| pair[0].source_start + pair[0].length <= pair[1].source_start));
+        }
+
+        // maps only go one way.
+        assert_eq!(composed_map(&almanac.maps, "location", "seed"), None);
+        assert_eq!(map_path(&almanac.maps, "location", "seed"), None);
+        assert_eq!(composed_map(&almanac.maps, "seed", "colour"), None);
+    }
+
+    #[test]
+    fn test_maps_out_of_order() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let mut sections = input.trim_end().split("\n\n").collect::<Vec<_>>();
+        sections[1..].reverse();
+        let reversed = sections.join("\n\n");
+        assert_eq!(part_one(&reversed), Some(35));
+        assert_eq!(part_two(&reversed), Some(46));
+
+        // more categories, and a map nobody needs.
+        let input = "seeds: 1 2
+
+seed-to-colour map:
+10 1 2
+
+size-to-location map:
+0 100 1
+
+colour-to-size map:
+100 11 1
+
+seed-to-weight map:
+0 0 100";
+        assert_eq!(part_one(input), Some(0));
+        assert_eq!(part_two(input), Some(0));
+        assert_eq!(part_two(input), brute_force_lowest_location(input));
+    }
+
+    #[test]
+    fn test_map_header_parse_error() {
+        let error = "seeds: 1\n\nseed-to-soil\n1 2 3"
+            .parse::<Almanac>()
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (3, 13));
+        assert_eq!(error.expected, "`map:`");
+
+        let error = "seeds: 1\n\nseed to soil map:\n1 2 3"
+            .parse::<Almanac>()
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (3, 6));
+        assert_eq!(error.expected, "`-to-`");
+    }
+
+    #[test]
+    fn test_inverse_map_sources() {
+        let map = Map {
+            source: "seed".to_string(),
+            destination: "soil".to_string(),
+            ranges: vec![
+                Range {
+                    source_start: 10,
+                    destination_start: 20,
+                    length: 5,
+                },
+                Range {
+                    source_start: 20,
+                    destination_start: 0,
+                    length: 5,
+                },
+            ],
+        };
+        let inverse = InverseMap::new(&map);
+        let sources = |value| {
+            let mut sources = inverse.sources(value).collect::<Vec<_>>();
+            sources.sort();
+            sources
+        };
+        // 20 is mapped away, but 10 is mapped onto it.
+        assert_eq!(sources(20), vec![10]);
+        // nothing is mapped onto 12, while 2 is left alone and 22 is mapped onto it too.
+        assert_eq!(sources(12), vec![]);
+        assert_eq!(sources(2), vec![2, 22]);
+        assert_eq!(sources(30), vec![30]);
+        assert_eq!(sources(u64::MAX), vec![u64::MAX]);
+        for value in 0..40 {
+            for source in sources(value) {
+                assert_eq!(map.mapped_value(source), value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_part_two_reverse_search() {
+        let almanac = advent_of_code::template::read_file("examples", DAY)
+            .parse::<AlmanacSeedRange>()
+            .unwrap();
+        assert_eq!(almanac.lowest_location_reverse(), Some(46));
+
+        let lowest = |input: &String, reverse: bool| {
+            let almanac = input.parse::<AlmanacSeedRange>().unwrap();
+            match reverse {
+                true => almanac.lowest_location_reverse(),
+                false => almanac.lowest_location(),
+            }
+        };
+        Property::new().cases(100).check(
+            |source| random_almanac(source, 1..=1, 2..=2),
+            |input| lowest(input, true),
+            |input| lowest(input, false),
+        );
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -483,4 +1229,23 @@ humidity-to-location map:
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(46));
     }
+
+    #[test]
+    fn test_part_two_variants() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        assert_eq!(map_by_map(&input), Some(46));
+    }
+
+    #[test]
+    fn test_generated_input() {
+        let input = generate(DAY, 100, 1).unwrap();
+        assert_eq!(part_one(&input), Some(12997915));
+        let almanac = input.parse::<AlmanacSeedRange>().unwrap();
+        assert_eq!(almanac.maps.len(), 7);
+        assert!(almanac
+            .seed_ranges
+            .iter()
+            .all(|range| range.length >= (1 << 32) / 400));
+        assert_eq!(part_two(&input), Some(2917578));
+    }
 }
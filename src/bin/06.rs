@@ -1,4 +1,9 @@
 use std::iter::zip;
+
+use advent_of_code::helper::math::{isqrt, CheckedIterator};
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
+
 advent_of_code::solution!(6);
 
 // Distance traveled
@@ -9,87 +14,103 @@ advent_of_code::solution!(6);
 // d = t * t_hold - t_hold^2
 // t_hold^2 - t * t_hold - d = 0
 // t_hold = (t +- sqrt(t^2 - 4 * d)) / 2
-pub fn solve_for_time_to_hold(total_time: i64, distance_to_beat: i64) -> (f64, f64) {
-    let sqrt = ((total_time * total_time - 4 * distance_to_beat) as f64).sqrt();
-    let t1 = (total_time as f64 - sqrt) / 2.0;
-    let t2 = (total_time as f64 + sqrt) / 2.0;
-    (t1, t2)
-}
+//
+// The roots are computed with an integer square root, which can be off by one from the true
+// boundary since it rounds down, so the boundary is corrected by checking the distances directly.
+/// Returns the shortest and longest hold times that travel strictly further than `distance_to_beat`,
+/// or [`None`] if the race can't be won.
+pub fn solve_for_time_to_hold(total_time: u64, distance_to_beat: u64) -> Option<(u64, u64)> {
+    let total_time = total_time as u128;
+    let distance_to_beat = distance_to_beat as u128;
+    let beats = |t_hold: u128| t_hold * (total_time - t_hold) > distance_to_beat;
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut lines = input.lines();
-    let times = lines
-        .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .filter(|val| !val.is_empty())
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
-    let distances = lines
-        .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .split_whitespace()
-        .filter(|val| !val.is_empty())
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
-    let mut ways_to_win_product = 1;
-    for (time, distance) in zip(times, distances) {
-        let (t1, t2) = solve_for_time_to_hold(time, distance);
-        // Round up t1 and down t2 to get the number of ways to win.
-        // But do so with a small epsilon to avoid rounding up to the same number if answer was a round number
-        let t1 = (t1 + 0.00001).ceil() as i64;
-        let t2 = (t2 - 0.00001).floor() as i64;
-        let ways_to_win = t2 - t1 + 1;
-        ways_to_win_product *= ways_to_win;
+    let discriminant = (total_time * total_time).checked_sub(4 * distance_to_beat)?;
+    let mut t1 = (total_time - isqrt(discriminant)) / 2;
+    while t1 <= total_time / 2 && !beats(t1) {
+        t1 += 1;
+    }
+    while t1 > 0 && beats(t1 - 1) {
+        t1 -= 1;
+    }
+    if t1 > total_time / 2 {
+        return None;
     }
-    Some(ways_to_win_product as u32)
+    // the distance is symmetric around t / 2.
+    let t2 = total_time - t1;
+    Some((u64::try_from(t1).ok()?, u64::try_from(t2).ok()?))
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut lines = input.lines();
-    let time = lines
-        .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .chars()
-        .filter(|val| !val.is_whitespace())
-        .fold("".to_string(), |mut acc, val| {
-            acc.push(val);
-            acc
-        })
-        .parse::<i64>()
-        .unwrap();
-
-    let distance = lines
-        .next()
-        .unwrap()
-        .split(':')
-        .nth(1)
-        .unwrap()
-        .chars()
-        .filter(|val| !val.is_whitespace())
-        .fold("".to_string(), |mut acc, val| {
-            acc.push(val);
-            acc
-        })
-        .parse::<i64>()
-        .unwrap();
-
-    let (t1, t2) = solve_for_time_to_hold(time, distance);
-    // Round up t1 and down t2 to get the number of ways to win.
-    // But do so with a small epsilon to avoid rounding up to the same number if answer was a round number
-    let t1 = (t1 + 0.00001).ceil() as i64;
-    let t2 = (t2 - 0.00001).floor() as i64;
-    let ways_to_win = t2 - t1 + 1;
-    Some(ways_to_win as u32)
+/// Number of hold times that win the race.
+fn ways_to_win(total_time: u64, distance_to_beat: u64) -> u64 {
+    solve_for_time_to_hold(total_time, distance_to_beat).map_or(0, |(t1, t2)| t2 - t1 + 1)
+}
+
+/// Parses line `index` of the input as `<key>: <values>`, parsing the values with `values`.
+fn parse_line<T>(
+    input: &str,
+    index: usize,
+    key: &str,
+    values: impl FnOnce(&mut Parser) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    let Some(mut line) = Parser::new(input).lines().nth(index) else {
+        let mut end_of_input = Parser::new(input);
+        end_of_input.take_while(|_| true);
+        return Err(end_of_input.error(format!("a `{key}:` line")));
+    };
+    let (_, values) = line.key_value(":", |p| p.literal(key), values)?;
+    line.end()?;
+    Ok(values)
+}
+
+/// Parses the times and distances of the races, as `(time, distance)` pairs.
+fn parse_races(input: &str) -> Result<Vec<(u64, u64)>, ParseError> {
+    let numbers = |p: &mut Parser| p.many(|p| p.number::<u64>());
+    let times = parse_line(input, 0, "Time", numbers)?;
+    let distances = parse_line(input, 1, "Distance", |p| {
+        let mut start = *p;
+        start.skip_spaces();
+        let distances = numbers(p)?;
+        if distances.len() != times.len() {
+            return Err(start.error(format!("{} distances, one per race", times.len())));
+        }
+        Ok(distances)
+    })?;
+    Ok(zip(times, distances).collect())
+}
+
+/// Parses the time and distance of the single race, ignoring the spaces between digits.
+fn parse_kerned_race(input: &str) -> Result<(u64, u64), ParseError> {
+    let kerned_number = |p: &mut Parser| {
+        p.skip_spaces();
+        let start = *p;
+        let digits = p.take_while(|c| c.is_ascii_digit() || c == ' ' || c == '\t');
+        let number = digits.split_whitespace().collect::<String>();
+        if number.is_empty() {
+            return Err(start.error("a number"));
+        }
+        number
+            .parse::<u64>()
+            .map_err(|_| start.error("a number fitting in `u64`"))
+    };
+    let time = parse_line(input, 0, "Time", kerned_number)?;
+    let distance = parse_line(input, 1, "Distance", kerned_number)?;
+    Ok((time, distance))
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let races = parse_races(input).unwrap();
+    Some(
+        races
+            .into_iter()
+            .map(|(time, distance)| ways_to_win(time, distance))
+            .checked_product()
+            .unwrap(),
+    )
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    let (time, distance) = parse_kerned_race(input).unwrap();
+    Some(ways_to_win(time, distance))
 }
 
 #[cfg(test)]
@@ -107,4 +128,83 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(71503));
     }
+
+    #[test]
+    fn test_solve_for_time_to_hold() {
+        assert_eq!(solve_for_time_to_hold(7, 9), Some((2, 5)));
+        // exact roots at 10 and 20 must not count as wins.
+        assert_eq!(solve_for_time_to_hold(30, 200), Some((11, 19)));
+        assert_eq!(solve_for_time_to_hold(4, 4), None);
+        assert_eq!(solve_for_time_to_hold(3, 100), None);
+        assert_eq!(solve_for_time_to_hold(0, 0), None);
+        assert_eq!(solve_for_time_to_hold(2, 0), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_ways_to_win_matches_brute_force() {
+        for time in 0..60 {
+            for distance in 0..(time * time / 4 + 2) {
+                let expected = (0..=time)
+                    .filter(|t_hold| t_hold * (time - t_hold) > distance)
+                    .count() as u64;
+                assert_eq!(ways_to_win(time, distance), expected);
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Overflow")]
+    fn test_part_one_overflow() {
+        part_one("Time: 8589934592 8589934592\nDistance: 0 0");
+    }
+
+    #[test]
+    fn test_parse_races() {
+        let input = "Time:      7  15   30\nDistance:  9  40  200\n";
+        assert_eq!(parse_races(input), Ok(vec![(7, 9), (15, 40), (30, 200)]));
+        assert_eq!(parse_kerned_race(input), Ok((71530, 940200)));
+    }
+
+    #[test]
+    fn test_parse_races_error() {
+        let error = parse_races("Time: 7 15\nDistance: 9").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 11));
+        assert_eq!(error.expected, "2 distances, one per race");
+
+        let error = parse_races("Time: 7 1x5\nDistance: 9 40").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 10));
+        assert_eq!(error.expected, "end of line");
+
+        let error = parse_races("Time: 7").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 8));
+        assert_eq!(error.expected, "a `Distance:` line");
+
+        let error = parse_kerned_race("Time: 7 2 3\nDistance 9").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 10));
+        assert_eq!(error.expected, "`:`");
+
+        let error = parse_kerned_race("Time: 99999999999 99999999999\nDistance: 9").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 7));
+        assert_eq!(error.expected, "a number fitting in `u64`");
+
+        let error = parse_kerned_race("Time: 7\nDistance: x").unwrap_err();
+        assert_eq!((error.line, error.column), (2, 11));
+        assert_eq!(error.expected, "a number");
+    }
+
+    #[test]
+    fn test_ways_to_win_large_race() {
+        // t^2 overflows both i64 and the 53 bits of precision of f64.
+        let time = 4_000_000_000_u64;
+        let t_hold = 1_000_000_000_u64;
+        let distance = t_hold * (time - t_hold);
+        assert_eq!(
+            solve_for_time_to_hold(time, distance),
+            Some((t_hold + 1, time - t_hold - 1))
+        );
+        assert_eq!(
+            solve_for_time_to_hold(time, distance - 1),
+            Some((t_hold, time - t_hold))
+        );
+    }
 }
@@ -1,19 +1,121 @@
-use std::cmp::Ordering;
-use std::iter::zip;
-
-use itertools::Itertools;
+use advent_of_code::helper::math::Overflow;
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
 
 advent_of_code::solution!(7);
 
-fn card_to_i8(card: char, j_value: i8) -> i8 {
-    match card {
-        'A' => 14,
-        '2'..='9' => card.to_digit(10).unwrap() as i8,
-        'T' => 10,
-        'J' => j_value,
-        'Q' => 12,
-        'K' => 13,
-        _ => panic!("Invalid card: {}", card),
+/// The most cards a hand can have, for its sort key to fit in an [`u32`].
+const MAX_HAND_LENGTH: usize = 7;
+
+/// The rules of a game of Camel Cards, built with [`Rules::new`] so that hands fit in sort keys.
+#[derive(Debug)]
+struct Rules {
+    /// The cards, from weakest to strongest. At most 14 cards, so that cards fit in a nibble.
+    ranking: &'static str,
+    /// A card that stands for whichever card makes the strongest hand type. It ranks below every
+    /// other card when comparing cards.
+    wildcard: Option<char>,
+    /// At most [`MAX_HAND_LENGTH`].
+    hand_length: usize,
+}
+
+impl Rules {
+    const STANDARD: Rules = Rules::new("23456789TJQKA", None, 5);
+
+    const JOKERS: Rules = Rules::new("23456789TJQKA", Some('J'), 5);
+
+    /// Panics with more than [`MAX_HAND_LENGTH`] cards per hand, or more than 14 cards in
+    /// `ranking`, since their sort keys would overflow or collide.
+    const fn new(ranking: &'static str, wildcard: Option<char>, hand_length: usize) -> Rules {
+        assert!(
+            hand_length <= MAX_HAND_LENGTH,
+            "hands of more than 7 cards don't fit in a sort key"
+        );
+        // `chars` isn't const, so cards are counted by their leading bytes.
+        let (bytes, mut index, mut cards) = (ranking.as_bytes(), 0, 0);
+        while index < bytes.len() {
+            if bytes[index] & 0xc0 != 0x80 {
+                cards += 1;
+            }
+            index += 1;
+        }
+        assert!(cards <= 14, "more than 14 cards don't fit in a nibble");
+        Rules {
+            ranking,
+            wildcard,
+            hand_length,
+        }
+    }
+
+    /// The value of a card, 1 being reserved for the wildcard.
+    fn card_value(&self, card: char) -> Option<i8> {
+        if self.wildcard == Some(card) {
+            return Some(1);
+        }
+        self.ranking
+            .chars()
+            .position(|c| c == card)
+            .map(|i| i as i8 + 2)
+    }
+
+    fn hand_type(&self, cards: impl IntoIterator<Item = char>) -> HandType {
+        // counts per card value, unknown cards counting as 0.
+        let mut counts = [0_usize; 16];
+        for card in cards {
+            counts[self.card_value(card).unwrap_or(0) as usize] += 1;
+        }
+        let wildcards = counts[1];
+        // only the two largest groups matter to the hand type.
+        let (mut largest, mut second) = (0, 0);
+        for &count in counts.iter().skip(2) {
+            if count > largest {
+                (largest, second) = (count, largest);
+            } else if count > second {
+                second = count;
+            }
+        }
+        // wildcards are always best used to grow the largest group.
+        HandType::from_signature(&[largest + wildcards, second])
+    }
+
+    /// Parses the cards of a hand, checking that there are [`Rules::hand_length`] known cards.
+    fn parse_cards<'a>(&self, parser: &mut Parser<'a>) -> Result<&'a str, ParseError> {
+        parser.skip_spaces();
+        let start = *parser;
+        let mut length = 0;
+        while !parser.is_empty() && !parser.rest().starts_with(char::is_whitespace) {
+            let card_start = *parser;
+            if length == self.hand_length {
+                return Err(card_start.error(format!("{} cards", self.hand_length)));
+            }
+            let card = parser.next_char().unwrap_or_default();
+            if self.card_value(card).is_none() {
+                let wildcard = self.wildcard.filter(|&card| !self.ranking.contains(card));
+                let cards = self.ranking.chars().chain(wildcard).collect::<String>();
+                return Err(card_start.error(format!("a card among `{cards}`")));
+            }
+            length += 1;
+        }
+        if length != self.hand_length {
+            return Err(parser.error(format!("{} cards", self.hand_length)));
+        }
+        Ok(&start.rest()[..start.rest().len() - parser.rest().len()])
+    }
+
+    /// Parses the cards of a hand into their [`sort_key`].
+    fn parse_hand_key(&self, parser: &mut Parser) -> Result<u32, ParseError> {
+        let cards = self.parse_cards(parser)?;
+        let values = cards.chars().map(|card| self.card_value(card).unwrap_or(0));
+        Ok(sort_key(self.hand_type(cards.chars()), values))
+    }
+
+    /// Same as [`Rules::parse_hand_key`], for a hand on its own.
+    #[cfg(test)]
+    fn hand_key(&self, cards: &str) -> Result<u32, ParseError> {
+        let mut parser = Parser::new(cards);
+        let key = self.parse_hand_key(&mut parser)?;
+        parser.end()?;
+        Ok(key)
     }
 }
 
@@ -28,108 +130,71 @@ enum HandType {
     HighCard = 1,
 }
 
-#[derive(Debug, Eq, PartialEq)]
-struct Hand {
-    cards: Vec<i8>,
-    hand_type: HandType,
-    bid: i32,
-}
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if (self.hand_type) as u8 == other.hand_type as u8 {
-            for (mine, theirs) in zip(&self.cards, &other.cards) {
-                if mine != theirs {
-                    return mine.cmp(theirs);
-                }
-            }
+impl HandType {
+    /// Classifies a hand from the sizes of its groups of equal cards, largest first.
+    fn from_signature(signature: &[usize]) -> HandType {
+        let largest = signature.first().copied().unwrap_or(0);
+        let second = signature.get(1).copied().unwrap_or(0);
+        match (largest, second) {
+            (5.., _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2..) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2..) => HandType::TwoPairs,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
         }
-        self.hand_type.cmp(&other.hand_type)
     }
 }
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-#[derive(Debug, Eq, PartialEq)]
-struct HandParseError;
 
-fn hand_from_str(s: &str, with_jokers: bool) -> Result<Hand, HandParseError> {
-    if let Some((cards, bid)) = s.split_once(' ') {
-        let cards = cards
-            .chars()
-            .map(|c| card_to_i8(c, if with_jokers { 1 } else { 11 }))
-            .collect_vec();
-        let bid = bid.parse::<i32>().unwrap();
-        // count cards by value
-        let mut counts = cards.iter().counts();
-        let mut joker_count = 0;
-        if with_jokers {
-            joker_count = counts.remove(&1).unwrap_or(0);
-        }
-        let mut hand_type = HandType::HighCard;
-        match counts.len() {
-            0 | 1 => hand_type = HandType::FiveOfAKind,
-            2 => {
-                let (&size1, &size2) = counts.values().collect_tuple().unwrap();
-                if size1 == 4 || size2 == 4 || size1 + joker_count == 4 || size2 + joker_count == 4
-                {
-                    hand_type = HandType::FourOfAKind;
-                } else {
-                    hand_type = HandType::FullHouse;
-                }
-            }
-            3 => {
-                let (&size1, &size2, &size3) = counts.values().collect_tuple().unwrap();
-                if size1 == 3
-                    || size2 == 3
-                    || size3 == 3
-                    || size1 + joker_count == 3
-                    || size2 + joker_count == 3
-                    || size3 + joker_count == 3
-                {
-                    hand_type = HandType::ThreeOfAKind;
-                } else {
-                    hand_type = HandType::TwoPairs;
-                }
-            }
-            4 => hand_type = HandType::OnePair,
-            _ => {}
-        }
-        Ok(Hand {
-            cards,
-            hand_type,
-            bid,
-        })
-    } else {
-        Err(HandParseError)
-    }
+/// Packs a hand into a key sorting hands from weakest to strongest: the hand type in the high
+/// nibble, then one nibble per card value.
+fn sort_key(hand_type: HandType, values: impl IntoIterator<Item = i8>) -> u32 {
+    values
+        .into_iter()
+        .fold(hand_type as u32, |key, value| key << 4 | value as u32)
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let hands = input
+/// Parses one `<cards> <bid>` hand per line, as `(key, bid)` pairs.
+fn parse_hands(input: &str, rules: &Rules) -> Result<Vec<(u32, u64)>, ParseError> {
+    Parser::new(input)
         .lines()
-        .map(|line| hand_from_str(line, false).unwrap())
-        .sorted()
-        .enumerate()
-        .map(|(i, hand)| (i + 1) as u32 * hand.bid as u32);
+        .map(|mut line| {
+            let key = rules.parse_hand_key(&mut line)?;
+            let bid = line.number::<u64>()?;
+            line.end()?;
+            Ok((key, bid))
+        })
+        .collect()
+}
 
-    Some(hands.sum())
+/// Sums the bid of each hand times its rank, from 1 for the weakest hand.
+fn total_winnings(input: &str, rules: &Rules) -> Result<u64, Overflow> {
+    let mut hands = parse_hands(input, rules).unwrap();
+    hands.sort_unstable();
+    (1_u64..)
+        .zip(hands)
+        .try_fold(0_u64, |total, (rank, (_, bid))| {
+            rank.checked_mul(bid)
+                .and_then(|winnings| total.checked_add(winnings))
+                .ok_or(Overflow)
+        })
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let hands = input
-        .lines()
-        .map(|line| hand_from_str(line, true).unwrap())
-        .sorted()
-        .enumerate()
-        .map(|(i, hand)| (i + 1) as u32 * hand.bid as u32);
+pub fn part_one(input: &str) -> Option<u64> {
+    Some(total_winnings(input, &Rules::STANDARD).unwrap())
+}
 
-    Some(hands.sum())
+pub fn part_two(input: &str) -> Option<u64> {
+    Some(total_winnings(input, &Rules::JOKERS).unwrap())
 }
 
 #[cfg(test)]
 mod tests {
+    use advent_of_code::helper::property::Property;
+    use advent_of_code::template::input_gen::generate;
+    use itertools::Itertools;
+
     use super::*;
 
     #[test]
@@ -144,6 +209,68 @@ mod tests {
         assert_eq!(result, Some(5905));
     }
 
+    #[test]
+    fn test_generated_input() {
+        let input = generate(DAY, 1000, 1).unwrap();
+        assert_eq!(part_one(&input), Some(246908620));
+        assert_eq!(part_two(&input), Some(248797856));
+
+        // ranks the hands by brute-forced hand types, then card values, instead of sort keys.
+        let reference = |rules: &Rules| {
+            let hands = input
+                .lines()
+                .map(|line| {
+                    let (cards, bid) = line.split_once(' ').unwrap();
+                    let cards = cards.chars().collect_vec();
+                    let values = cards
+                        .iter()
+                        .map(|&card| rules.card_value(card))
+                        .collect_vec();
+                    let bid = bid.parse::<u64>().unwrap();
+                    (brute_force_hand_type(&cards, rules), values, bid)
+                })
+                .sorted();
+            (1..).zip(hands).map(|(rank, (_, _, bid))| rank * bid).sum()
+        };
+        assert_eq!(part_one(&input), Some(reference(&Rules::STANDARD)));
+        assert_eq!(part_two(&input), Some(reference(&Rules::JOKERS)));
+    }
+
+    /// A parsed hand, in readable form. Solving only needs [`Rules::parse_hand_key`], which
+    /// doesn't allocate.
+    #[derive(Debug, Eq, PartialEq)]
+    struct Hand {
+        cards: Vec<i8>,
+        hand_type: HandType,
+        bid: i32,
+    }
+    impl Ord for Hand {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            let key = |hand: &Hand| sort_key(hand.hand_type, hand.cards.iter().copied());
+            key(self).cmp(&key(other))
+        }
+    }
+    impl PartialOrd for Hand {
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    fn hand_from_str(s: &str, rules: &Rules) -> Result<Hand, ParseError> {
+        let mut parser = Parser::new(s);
+        let cards = rules.parse_cards(&mut parser)?;
+        let bid = parser.number::<i32>()?;
+        parser.end()?;
+        Ok(Hand {
+            cards: cards
+                .chars()
+                .filter_map(|card| rules.card_value(card))
+                .collect(),
+            hand_type: rules.hand_type(cards.chars()),
+            bid,
+        })
+    }
+
     #[test]
     fn test_hand_ordering() {
         let hand1 = Hand {
@@ -174,7 +301,7 @@ mod tests {
     fn test_hand_parsing() {
         let input = "AAAAJ 1234";
         assert_eq!(
-            hand_from_str(input, false),
+            hand_from_str(input, &Rules::STANDARD),
             Ok(Hand {
                 cards: vec![14, 14, 14, 14, 11],
                 hand_type: HandType::FourOfAKind,
@@ -184,7 +311,7 @@ mod tests {
 
         let input = "AAAAJ 1234";
         assert_eq!(
-            hand_from_str(input, true),
+            hand_from_str(input, &Rules::JOKERS),
             Ok(Hand {
                 cards: vec![14, 14, 14, 14, 1],
                 hand_type: HandType::FiveOfAKind,
@@ -192,4 +319,147 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_hand_key() {
+        // the hand type is the high nibble.
+        let hand_type = |rules: &Rules, cards| rules.hand_key(cards).unwrap() >> 20;
+        for (cards, standard, jokers) in [
+            ("AAAAJ", HandType::FourOfAKind, HandType::FiveOfAKind),
+            ("2345J", HandType::HighCard, HandType::OnePair),
+            ("T55J5", HandType::ThreeOfAKind, HandType::FourOfAKind),
+            ("KTJJT", HandType::TwoPairs, HandType::FourOfAKind),
+            ("QQQJA", HandType::ThreeOfAKind, HandType::FourOfAKind),
+            ("JJJJJ", HandType::FiveOfAKind, HandType::FiveOfAKind),
+        ] {
+            assert_eq!(
+                hand_type(&Rules::STANDARD, cards),
+                standard as u32,
+                "{cards}"
+            );
+            assert_eq!(hand_type(&Rules::JOKERS, cards), jokers as u32, "{cards}");
+        }
+        assert_eq!(Rules::STANDARD.hand_key("AAAAT"), Ok(0x6e_eeea));
+        assert_eq!(Rules::JOKERS.hand_key("AAAAJ"), Ok(0x7e_eee1));
+        let error = Rules::STANDARD.hand_key("AAAAAA").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (6, "5 cards"));
+        let error = Rules::STANDARD.hand_key("AAAA").unwrap_err();
+        assert_eq!((error.column, error.expected.as_str()), (5, "5 cards"));
+    }
+
+    #[test]
+    fn test_hand_parse_error() {
+        let error = |s| {
+            let error = parse_hands(s, &Rules::STANDARD).unwrap_err();
+            (error.column, error.expected)
+        };
+        assert_eq!(error("AAAA 1"), (5, "5 cards".to_string()));
+        assert_eq!(
+            error("AAAAX 1"),
+            (5, "a card among `23456789TJQKA`".to_string())
+        );
+        assert_eq!(error("AAAAJ x"), (7, "a number".to_string()));
+        assert_eq!(error("AAAAJ"), (6, "a number".to_string()));
+
+        let error = parse_hands("32T3K 765\nT55J5 684 1", &Rules::JOKERS).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 11));
+        assert_eq!(error.expected, "end of line");
+        let rules = Rules::new("ABC", Some('*'), 3);
+        let error = parse_hands("C*Cx 7", &rules).unwrap_err();
+        assert_eq!(error.expected, "3 cards");
+        let error = parse_hands("C*D 7", &rules).unwrap_err();
+        assert_eq!(error.expected, "a card among `ABC*`");
+    }
+
+    #[test]
+    fn test_total_winnings_overflow() {
+        let input = "23456 9223372036854775807\nAAAAA 1";
+        assert_eq!(
+            total_winnings(input, &Rules::STANDARD),
+            Ok(9223372036854775807 + 2)
+        );
+        let input = "23456 1\nAAAAA 9223372036854775808";
+        assert_eq!(total_winnings(input, &Rules::STANDARD), Err(Overflow));
+    }
+
+    #[test]
+    fn test_other_rules() {
+        let rules = Rules::new("ABC", Some('*'), 3);
+        // three of a kind, then C, *, C.
+        assert_eq!(rules.hand_key("C*C"), Ok(0x4414));
+        // high card, then B, C, A.
+        assert_eq!(rules.hand_key("BCA"), Ok(0x1342));
+    }
+
+    #[test]
+    #[should_panic(expected = "don't fit in a sort key")]
+    fn test_rules_hand_length_limit() {
+        Rules::new("23456789TJQKA", None, MAX_HAND_LENGTH + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "don't fit in a nibble")]
+    fn test_rules_ranking_limit() {
+        Rules::new("0123456789TJQKA", None, 5);
+    }
+
+    /// Classifies a hand by trying every card in place of each wildcard.
+    fn brute_force_hand_type(cards: &[char], rules: &Rules) -> HandType {
+        let Some(position) = cards.iter().position(|&card| Some(card) == rules.wildcard) else {
+            let plain = Rules::new(rules.ranking, None, rules.hand_length);
+            return plain.hand_type(cards.iter().copied());
+        };
+        rules
+            .ranking
+            .chars()
+            .filter(|&card| Some(card) != rules.wildcard)
+            .map(|replacement| {
+                let mut replaced = cards.to_vec();
+                replaced[position] = replacement;
+                brute_force_hand_type(&replaced, rules)
+            })
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_wildcard_classification() {
+        let alphabet = ['J', '2', 'K', 'A'];
+        for hand in (0..5).map(|_| alphabet).multi_cartesian_product() {
+            assert_eq!(
+                Rules::JOKERS.hand_type(hand.iter().copied()),
+                brute_force_hand_type(&hand, &Rules::JOKERS),
+                "{hand:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wildcard_classification_other_rules() {
+        let rules = Rules::new("ABC", Some('*'), 3);
+        assert_eq!(
+            brute_force_hand_type(&['C', 'B', 'C'], &rules),
+            HandType::OnePair
+        );
+        let alphabet = ['*', 'A', 'B', 'C'];
+        for hand in (0..3).map(|_| alphabet).multi_cartesian_product() {
+            assert_eq!(
+                rules.hand_type(hand.iter().copied()),
+                brute_force_hand_type(&hand, &rules),
+                "{hand:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_joker_classification_matches_brute_force() {
+        let cards = Rules::STANDARD.ranking.chars().collect_vec();
+        // jokers first, so that hands shrink towards jokers.
+        let cards = [vec!['J'], cards].concat();
+        Property::new().check(
+            |source| (0..5).map(|_| *source.choose(&cards)).collect_vec(),
+            |hand| Rules::JOKERS.hand_type(hand.iter().copied()),
+            |hand| brute_force_hand_type(hand, &Rules::JOKERS),
+        );
+    }
 }
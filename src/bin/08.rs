@@ -1,140 +1,275 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::io;
 
+use advent_of_code::helper::cycle::find_cycle;
+use advent_of_code::helper::graph::{dump_requested, write_dot, Graph};
+use advent_of_code::helper::math::crt;
+use advent_of_code::helper::parser::Parser;
+use advent_of_code::helper::ParseError;
 use itertools::Itertools;
-use primes::{PrimeSet, Sieve};
 
 advent_of_code::solution!(8);
 
-fn base26_to_usize(s: &str) -> usize {
-    let mut result = 0;
-    for c in s.chars() {
-        result *= 26;
-        result += (c as u8 - b'A') as usize;
-    }
-    result
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Turn {
+    Left,
+    Right,
 }
 
-fn parse_line(line: &str) -> (usize, (usize, usize)) {
-    (
-        base26_to_usize(&line[0..3]),
-        (
-            base26_to_usize(&line[7..10]),
-            base26_to_usize(&line[12..15]),
-        ),
-    )
+#[derive(Debug, PartialEq)]
+struct Node(usize, usize);
+
+/// The network of nodes. Labels are interned, nodes are referred to by their index in `labels`.
+#[derive(Debug, PartialEq)]
+struct Network<'a> {
+    turns: Vec<Turn>,
+    labels: Vec<&'a str>,
+    /// The index of each label in `labels`.
+    ids: HashMap<&'a str, usize>,
+    nodes: Vec<Node>,
 }
 
-fn find_cycle_with_steps_for_end_nodes(
-    start_node: usize,
-    nodes: &HashMap<usize, Node>,
-    directions: impl Iterator<Item = char>,
-    direction_count: usize,
-) -> Vec<u64> {
-    let mut current_node_address = start_node;
-    let mut steps = 0;
-    let mut visited_nodes = HashSet::new();
-    let mut end_steps = Vec::new();
-    for direction in directions {
-        let node_position_in_direction_cycle = steps % direction_count;
-        steps += 1;
-        visited_nodes.insert((current_node_address, node_position_in_direction_cycle));
-        let current_node = nodes.get(&current_node_address).unwrap();
-        current_node_address = match direction {
-            'L' => current_node.0,
-            'R' => current_node.1,
-            _ => panic!("Invalid direction"),
-        };
-        if current_node_address % 26 == 25 {
-            // End node
-            end_steps.push(steps as u64);
+impl<'a> Network<'a> {
+    fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let mut lines = Parser::new(input).lines();
+        let mut first_line = lines.next().unwrap_or(Parser::new(input));
+        let turns = first_line.many(|parser| {
+            if parser.eat("L") {
+                Ok(Turn::Left)
+            } else if parser.eat("R") {
+                Ok(Turn::Right)
+            } else {
+                Err(parser.error("`L` or `R`"))
+            }
+        })?;
+        if turns.is_empty() {
+            return Err(first_line.error("`L` or `R`"));
         }
-        if visited_nodes.contains(&(current_node_address, node_position_in_direction_cycle + 1)) {
-            // Cycle
-            break;
+        first_line.end()?;
+
+        // each label is parsed along with its position, to report labels that are never defined.
+        let label = |parser: &mut Parser<'a>| {
+            parser.skip_spaces();
+            let position = *parser;
+            Ok((parser.word()?, position))
+        };
+        let definitions = lines
+            .filter(|line| !line.is_empty())
+            .map(|mut line| {
+                let definition = line.key_value("=", label, |parser| {
+                    parser.literal("(")?;
+                    let left = label(parser)?;
+                    parser.literal(",")?;
+                    let right = label(parser)?;
+                    parser.literal(")")?;
+                    Ok((left, right))
+                })?;
+                line.end()?;
+                Ok(definition)
+            })
+            .collect::<Result<Vec<_>, ParseError>>()?;
+
+        let mut ids: HashMap<&'a str, usize> = HashMap::new();
+        let mut labels = Vec::new();
+        let mut first_references = Vec::new();
+        let mut intern = |(label, position): (&'a str, Parser<'a>)| {
+            *ids.entry(label).or_insert_with(|| {
+                labels.push(label);
+                first_references.push(position);
+                labels.len() - 1
+            })
+        };
+        let definitions = definitions
+            .into_iter()
+            .map(|(from, (left, right))| (intern(from), from.1, Node(intern(left), intern(right))))
+            .collect_vec();
+
+        let mut nodes = first_references.iter().map(|_| None).collect_vec();
+        for (from, position, node) in definitions {
+            if nodes[from].replace(node).is_some() {
+                return Err(position.error("a node that is not defined yet"));
+            }
         }
+        let nodes = nodes
+            .into_iter()
+            .zip(first_references)
+            .map(|(node, reference)| node.ok_or_else(|| reference.error("a defined node")))
+            .collect::<Result<_, _>>()?;
+        Ok(Network {
+            turns,
+            labels,
+            ids,
+            nodes,
+        })
     }
-    end_steps
-}
 
-fn least_common_multiple(mut numbers: Vec<u64>) -> u64 {
-    let mut prime_set = Sieve::new();
-    let mut primes = prime_set.iter();
-    let mut factors = Vec::new();
-    loop {
-        let next_prime = primes.next().unwrap();
-        loop {
-            let mut found = false;
-            for number in &mut numbers {
-                if *number % next_prime == 0 {
-                    *number /= next_prime;
-                    found = true;
-                }
+    fn id(&self, label: &str) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    /// The network as a graph, with start and end nodes highlighted.
+    fn graph(&self, rules: &Rules) -> Graph {
+        let mut graph = Graph::new("network");
+        for (&label, node) in self.labels.iter().zip(self.nodes.iter()) {
+            if label.ends_with(rules.start_suffix) {
+                graph.node(label, &[("style", "filled"), ("fillcolor", "palegreen")]);
+            } else if label.ends_with(rules.end_suffix) {
+                graph.node(label, &[("style", "filled"), ("fillcolor", "lightcoral")]);
+            } else {
+                graph.node(label, &[]);
             }
-            if found {
-                factors.push(next_prime);
+            let (left, right) = (self.labels[node.0], self.labels[node.1]);
+            if left == right {
+                graph.edge(label, left, Some("LR"));
             } else {
-                break;
+                graph
+                    .edge(label, left, Some("L"))
+                    .edge(label, right, Some("R"));
             }
         }
-        if numbers.iter().all(|n| *n == 1) {
-            break;
+        graph
+    }
+
+    /// Writes the whole network, then the part reachable from each start node, next to the input.
+    fn dump_dot(&self, rules: &Rules) -> io::Result<()> {
+        let graph = self.graph(rules);
+        eprintln!("wrote {}", write_dot(DAY, None, &graph)?.display());
+        for &label in self.labels.iter() {
+            if label.ends_with(rules.start_suffix) {
+                let reachable = graph.reachable_from(&format!("reachable from {label}"), &[label]);
+                eprintln!(
+                    "wrote {}",
+                    write_dot(DAY, Some(label), &reachable)?.display()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn next(&self, node: usize, turn: Turn) -> usize {
+        match turn {
+            Turn::Left => self.nodes[node].0,
+            Turn::Right => self.nodes[node].1,
         }
     }
-    factors.iter().product()
 }
 
-pub fn partial_cartesian<T: Clone>(a: Vec<Vec<T>>, b: &[T]) -> Vec<Vec<T>> {
-    a.into_iter()
-        .flat_map(|xs| {
-            b.iter()
-                .cloned()
-                .map(|y| {
-                    let mut vec = xs.clone();
-                    vec.push(y);
-                    vec
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect()
+/// Decides which nodes the ghosts start and end on, from the end of their labels.
+struct Rules {
+    start_suffix: &'static str,
+    end_suffix: &'static str,
 }
 
-pub fn cartesian_product<T: Clone>(lists: &Vec<Vec<T>>) -> Vec<Vec<T>> {
-    match lists.split_first() {
-        Some((first, rest)) => {
-            let init: Vec<Vec<T>> = first.iter().cloned().map(|n| vec![n]).collect();
+impl Rules {
+    const GHOSTS: Rules = Rules {
+        start_suffix: "A",
+        end_suffix: "Z",
+    };
+}
 
-            rest.iter()
-                .cloned()
-                .fold(init, |vec, list| partial_cartesian(vec, &list))
+/// The steps at which a ghost stands on an end node.
+///
+/// A ghost's state is its node and its position in the directions, so its walk always ends up in
+/// a cycle: it first walks `prefix_len` steps, then repeats the same `cycle_len` steps forever.
+#[derive(Debug, PartialEq)]
+struct Ghost {
+    prefix_len: u128,
+    cycle_len: u128,
+    /// End steps before entering the cycle, in increasing order.
+    prefix_ends: Vec<u128>,
+    /// End steps during the first walk through the cycle, which repeat every `cycle_len` steps.
+    cycle_ends: Vec<u128>,
+}
+
+impl Ghost {
+    fn new(start_node: usize, network: &Network, is_end: &[bool]) -> Ghost {
+        let turns = &network.turns;
+        let step = |&(node, turn_index): &(usize, usize)| {
+            (
+                network.next(node, turns[turn_index]),
+                (turn_index + 1) % turns.len(),
+            )
+        };
+        let (prefix_len, cycle_len) = find_cycle((start_node, 0), step);
+
+        let mut ghost = Ghost {
+            prefix_len: prefix_len as u128,
+            cycle_len: cycle_len as u128,
+            prefix_ends: Vec::new(),
+            cycle_ends: Vec::new(),
+        };
+        let mut state = (start_node, 0);
+        for steps in 0..prefix_len + cycle_len {
+            if is_end[state.0] {
+                if steps < prefix_len {
+                    ghost.prefix_ends.push(steps as u128);
+                } else {
+                    ghost.cycle_ends.push(steps as u128);
+                }
+            }
+            state = step(&state);
         }
-        None => {
-            vec![]
+        ghost
+    }
+
+    fn is_end_at(&self, steps: u128) -> bool {
+        if steps < self.prefix_len {
+            self.prefix_ends.contains(&steps)
+        } else {
+            let steps_in_cycle = self.prefix_len + (steps - self.prefix_len) % self.cycle_len;
+            self.cycle_ends.contains(&steps_in_cycle)
         }
     }
 }
 
-#[derive(Debug)]
-struct Node(usize, usize);
-pub fn part_one(input: &str) -> Option<i32> {
-    let mut lines = input.lines();
-    let directions = lines.next().unwrap().chars().cycle();
-    let mut nodes: HashMap<usize, Node> = HashMap::new();
-
-    for line in lines.filter(|l| !l.is_empty()) {
-        let (from, (to1, to2)) = parse_line(line);
-        nodes.insert(from, Node(to1, to2));
-    }
-    let mut current = base26_to_usize("AAA");
-    let target = base26_to_usize("ZZZ");
-    let mut steps = 0;
-    for direction in directions {
+/// Returns the first step (after the start) at which every ghost stands on an end node.
+fn first_simultaneous_arrival(ghosts: &[Ghost]) -> Option<u128> {
+    let slowest = ghosts.iter().max_by_key(|ghost| ghost.prefix_len)?;
+
+    // before the slowest ghost enters its cycle, any common step is one of its prefix ends.
+    if let Some(&steps) = slowest
+        .prefix_ends
+        .iter()
+        .filter(|&&steps| steps > 0)
+        .find(|&&steps| ghosts.iter().all(|ghost| ghost.is_end_at(steps)))
+    {
+        return Some(steps);
+    }
+
+    // after that, every ghost restricts the step to one residue modulo its cycle length per end
+    // node in its cycle. Combining them gives the possible residues modulo the lcm of the cycles.
+    let mut residues = vec![(0, 1)];
+    for ghost in ghosts {
+        residues = residues
+            .iter()
+            .flat_map(|&(residue, modulus)| {
+                ghost
+                    .cycle_ends
+                    .iter()
+                    .filter_map(move |&end| crt(residue, modulus, end, ghost.cycle_len))
+            })
+            .collect();
+        residues.sort_unstable();
+        residues.dedup();
+    }
+
+    let lower_bound = slowest.prefix_len.max(1);
+    residues
+        .iter()
+        .map(|&(residue, modulus)| {
+            lower_bound + (residue + modulus - lower_bound % modulus) % modulus
+        })
+        .min()
+}
+
+pub fn part_one(input: &str) -> Option<u64> {
+    let network = Network::parse(input).unwrap();
+    let mut current = network.id("AAA")?;
+    let target = network.id("ZZZ")?;
+    let mut steps = 0_u64;
+    for &turn in network.turns.iter().cycle() {
         steps += 1;
-        let current_node = nodes.get(&current).unwrap();
-        current = match direction {
-            'L' => current_node.0,
-            'R' => current_node.1,
-            _ => panic!("Invalid direction"),
-        };
+        current = network.next(current, turn);
         if current == target {
             break;
         }
@@ -142,37 +277,33 @@ pub fn part_one(input: &str) -> Option<i32> {
     Some(steps)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let mut lines = input.lines();
-    let directions = lines.next().unwrap().chars();
-    let direction_count = directions.clone().count();
-    let directions = directions.cycle();
-    let mut nodes: HashMap<usize, Node> = HashMap::new();
-    let mut starting_nodes: Vec<usize> = Vec::new();
-
-    for line in lines.filter(|l| !l.is_empty()) {
-        let (from, (to1, to2)) = parse_line(line);
-        nodes.insert(from, Node(to1, to2));
-        if from % 26 == 0 {
-            starting_nodes.push(from);
-        }
-    }
-    let potential_step_multiples = starting_nodes
+fn first_ghost_arrival(network: &Network, rules: &Rules) -> Option<u128> {
+    let is_end = network
+        .labels
         .iter()
-        .map(|n| {
-            find_cycle_with_steps_for_end_nodes(*n, &nodes, directions.clone(), direction_count)
-        })
+        .map(|label| label.ends_with(rules.end_suffix))
         .collect_vec();
-    let result = cartesian_product(&potential_step_multiples)
-        .into_iter()
-        .map(least_common_multiple)
-        .min()
-        .unwrap();
-    Some(result)
+    let ghosts = (0..network.labels.len())
+        .filter(|&node| network.labels[node].ends_with(rules.start_suffix))
+        .map(|start_node| Ghost::new(start_node, network, &is_end))
+        .collect_vec();
+    advent_of_code::debug!("ghosts: {ghosts:?}");
+    first_simultaneous_arrival(&ghosts)
+}
+
+pub fn part_two(input: &str) -> Option<u128> {
+    let network = Network::parse(input).unwrap();
+    if dump_requested() {
+        network.dump_dot(&Rules::GHOSTS).unwrap();
+    }
+    first_ghost_arrival(&network, &Rules::GHOSTS)
 }
 
 #[cfg(test)]
 mod tests {
+    use advent_of_code::helper::property::{Property, Source};
+    use advent_of_code::template::input_gen::generate;
+
     use super::*;
 
     #[test]
@@ -192,8 +323,155 @@ mod tests {
     }
 
     #[test]
-    fn test_base26_to_usize() {
-        assert_eq!(base26_to_usize("AAA"), 0);
-        assert_eq!(base26_to_usize("ZZZ"), 17575);
+    fn test_generated_input() {
+        // ghosts of 9, 9, 8, 8, 8 and 8 nodes, first ending after 8 or 7 steps, then every 8 or 7.
+        let input = generate(DAY, 50, 1).unwrap();
+        assert_eq!(part_one(&input), Some(8));
+        assert_eq!(part_two(&input), Some(56));
+    }
+
+    /// Walks all ghosts step by step, up to `limit` steps.
+    fn brute_force_part_two(input: &str, limit: u128) -> Option<u128> {
+        let network = Network::parse(input).unwrap();
+        let is_end = |node: usize| network.labels[node].ends_with('Z');
+        let mut ghosts = (0..network.labels.len())
+            .filter(|&node| network.labels[node].ends_with('A'))
+            .collect_vec();
+        for steps in 1..=limit {
+            let turn = network.turns[(steps - 1) as usize % network.turns.len()];
+            for ghost in ghosts.iter_mut() {
+                *ghost = network.next(*ghost, turn);
+            }
+            if ghosts.iter().all(|&ghost| is_end(ghost)) {
+                return Some(steps);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_first_simultaneous_arrival() {
+        // ends at steps 3, 7, 11, ...
+        let first = Ghost {
+            prefix_len: 0,
+            cycle_len: 4,
+            prefix_ends: vec![],
+            cycle_ends: vec![3],
+        };
+        // ends at step 1, then at 6, 9, 12, ...
+        let second = Ghost {
+            prefix_len: 5,
+            cycle_len: 3,
+            prefix_ends: vec![1],
+            cycle_ends: vec![6],
+        };
+        assert_eq!(first_simultaneous_arrival(&[first, second]), Some(15));
+    }
+
+    #[test]
+    fn test_part_two_first_arrival_in_prefix() {
+        // both ghosts end at step 1 but never again at the same time.
+        let input = "L\n\nAAA = (AAZ, AAZ)\nAAZ = (BBZ, BBZ)\nBBZ = (BBZ, BBZ)\nCCA = (CCZ, CCZ)\nCCZ = (CCB, CCB)\nCCB = (CCB, CCB)\n";
+        assert_eq!(part_two(input), Some(1));
+    }
+
+    /// A random network of 2 to 15 nodes, the first one being a start.
+    fn random_network(source: &mut Source) -> String {
+        let node_count = 2 + source.below(14) as usize;
+        let names = (0..node_count)
+            .map(|index| {
+                let suffix = match source.below(5) {
+                    _ if index == 0 => 'A',
+                    0 => 'A',
+                    1 => 'Z',
+                    _ => 'M',
+                };
+                format!(
+                    "{}{}{}",
+                    (b'B' + (index / 24) as u8) as char,
+                    (b'B' + (index % 24) as u8) as char,
+                    suffix
+                )
+            })
+            .collect_vec();
+        let directions = source
+            .vec(1..=7, |source| if source.bool() { 'R' } else { 'L' })
+            .into_iter()
+            .collect::<String>();
+        let mut input = format!("{directions}\n\n");
+        for name in names.iter() {
+            let left = source.choose(&names);
+            let right = source.choose(&names);
+            input.push_str(&format!("{name} = ({left}, {right})\n"));
+        }
+        input
+    }
+
+    #[test]
+    fn test_part_two_matches_brute_force() {
+        let limit = 10_000;
+        Property::new().cases(500).check(
+            random_network,
+            |input| part_two(input).filter(|&steps| steps <= limit),
+            |input| brute_force_part_two(input, limit),
+        );
+    }
+
+    #[test]
+    fn test_part_two_numeric_labels() {
+        let input = "LR\n\n11A = (11B, XXX)\n11B = (XXX, 11Z)\n11Z = (11B, XXX)\n22A = (22B, XXX)\n22B = (22C, 22C)\n22C = (22Z, 22Z)\n22Z = (22B, 22B)\nXXX = (XXX, XXX)\n";
+        assert_eq!(part_two(input), Some(6));
+    }
+
+    #[test]
+    fn test_network_parse() {
+        let network = Network::parse(
+            "RL\n\nstart=(b,  middle)\n  middle = ( end , b )\nb = (b, b)\nend = (end,end)\n",
+        )
+        .unwrap();
+        assert_eq!(network.turns, vec![Turn::Right, Turn::Left]);
+        assert_eq!(network.labels, vec!["start", "b", "middle", "end"]);
+        assert_eq!(network.nodes[0], Node(1, 2));
+        assert_eq!(network.nodes[2], Node(3, 1));
+
+        let rules = Rules {
+            start_suffix: "start",
+            end_suffix: "end",
+        };
+        assert_eq!(first_ghost_arrival(&network, &rules), Some(2));
+    }
+
+    #[test]
+    fn test_network_graph() {
+        let network = Network::parse(
+            "LR\n\nAAA = (BBB, ZZZ)\nBBB = (ZZZ, ZZZ)\nZZZ = (ZZZ, ZZZ)\nCCA = (CCA, CCA)\n",
+        )
+        .unwrap();
+        let graph = network.graph(&Rules::GHOSTS);
+        let dot = graph.to_string();
+        assert!(dot.contains("\"AAA\" [style=\"filled\", fillcolor=\"palegreen\"];"));
+        assert!(dot.contains("\"ZZZ\" [style=\"filled\", fillcolor=\"lightcoral\"];"));
+        assert!(dot.contains("\"AAA\" -> \"BBB\" [label=\"L\"];"));
+        assert!(dot.contains("\"AAA\" -> \"ZZZ\" [label=\"R\"];"));
+        assert!(dot.contains("\"BBB\" -> \"ZZZ\" [label=\"LR\"];"));
+
+        let reachable = graph
+            .reachable_from("reachable from CCA", &["CCA"])
+            .to_string();
+        assert!(reachable.contains("\"CCA\" -> \"CCA\""));
+        assert!(!reachable.contains("AAA"));
+    }
+
+    #[test]
+    fn test_network_parse_error() {
+        let error = Network::parse("LR\n\nAAA = (BBB, CCC)\nBBB = (BBB, BBB)\n").unwrap_err();
+        assert_eq!((error.line, error.column), (3, 13));
+        assert_eq!(error.expected, "a defined node");
+
+        let error = Network::parse("LRX\n\nAAA = (AAA, AAA)\n").unwrap_err();
+        assert_eq!((error.line, error.column), (1, 3));
+
+        let error = Network::parse("L\n\nAAA = (AAA, AAA)\nAAA = (AAA, AAA)\n").unwrap_err();
+        assert_eq!((error.line, error.column), (4, 1));
     }
 }
@@ -0,0 +1,199 @@
+//! Cycle detection for puzzles that ask for the state after a huge number of steps.
+//!
+//! Every detector takes an initial state and a `step` function producing the next state, and
+//! returns `(prefix_len, cycle_len)`: the number of steps before the first state that is part of
+//! the cycle, and the length of the cycle itself.
+//!
+//! The state space is assumed to be finite, otherwise these functions never return.
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Floyd's "tortoise and hare" cycle detection.
+/// Uses constant memory, but calls `step` roughly three times as often as [`find_cycle`].
+pub fn floyd<T, F>(start: T, mut step: F) -> (usize, usize)
+where
+    T: Clone + Eq,
+    F: FnMut(&T) -> T,
+{
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        hare = step(&hare);
+    }
+
+    // the distance from the start to the meeting point is a multiple of the cycle length,
+    // so walking from the start and from the meeting point in lockstep meets at the cycle start.
+    let mut prefix_len = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix_len += 1;
+    }
+
+    let mut cycle_len = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        cycle_len += 1;
+    }
+
+    (prefix_len, cycle_len)
+}
+
+/// Brent's cycle detection.
+/// Uses constant memory and usually fewer calls to `step` than [`floyd`].
+pub fn brent<T, F>(start: T, mut step: F) -> (usize, usize)
+where
+    T: Clone + Eq,
+    F: FnMut(&T) -> T,
+{
+    let mut power = 1;
+    let mut cycle_len = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == cycle_len {
+            tortoise = hare.clone();
+            power *= 2;
+            cycle_len = 0;
+        }
+        hare = step(&hare);
+        cycle_len += 1;
+    }
+
+    // put the hare one cycle length ahead, then advance both until they meet at the cycle start.
+    let mut tortoise = start.clone();
+    let mut hare = start;
+    for _ in 0..cycle_len {
+        hare = step(&hare);
+    }
+    let mut prefix_len = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix_len += 1;
+    }
+
+    (prefix_len, cycle_len)
+}
+
+/// Hash-based cycle detection.
+/// Calls `step` exactly `prefix_len + cycle_len` times, but keeps every visited state in memory.
+pub fn find_cycle<T, F>(start: T, step: F) -> (usize, usize)
+where
+    T: Clone + Eq + Hash,
+    F: FnMut(&T) -> T,
+{
+    let (_, prefix_len, cycle_len) = walk_until_repeat(start, step);
+    (prefix_len, cycle_len)
+}
+
+/// Returns the state reached after `n` applications of `step`, skipping over full cycles
+/// instead of simulating them.
+///
+/// # Example
+/// ```
+/// use advent_of_code::helper::cycle::nth_state;
+///
+/// // 0, 1, 2, 3, 4, 2, 3, 4, ...
+/// let state = nth_state(0_u32, |x| if *x == 4 { 2 } else { x + 1 }, 1_000_000_000);
+/// assert_eq!(state, 4);
+/// ```
+pub fn nth_state<T, F>(start: T, step: F, n: usize) -> T
+where
+    T: Clone + Eq + Hash,
+    F: FnMut(&T) -> T,
+{
+    let (mut states, prefix_len, cycle_len) = walk_until_repeat(start, step);
+    let index = if n < prefix_len {
+        n
+    } else {
+        prefix_len + (n - prefix_len) % cycle_len
+    };
+    states.swap_remove(index)
+}
+
+/// Steps through the sequence until a state repeats.
+/// Returns every distinct state in order of appearance, along with the prefix and cycle lengths.
+fn walk_until_repeat<T, F>(start: T, mut step: F) -> (Vec<T>, usize, usize)
+where
+    T: Clone + Eq + Hash,
+    F: FnMut(&T) -> T,
+{
+    let mut seen: HashMap<T, usize> = HashMap::new();
+    let mut states = vec![start.clone()];
+    let mut current = start;
+    loop {
+        let next = step(&current);
+        seen.insert(current, states.len() - 1);
+        if let Some(&first_seen) = seen.get(&next) {
+            let cycle_len = states.len() - first_seen;
+            return (states, first_seen, cycle_len);
+        }
+        states.push(next.clone());
+        current = next;
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{brent, find_cycle, floyd, nth_state};
+
+    /// 0 -> 1 -> ... -> prefix_len -> ... -> prefix_len + cycle_len - 1 -> prefix_len -> ...
+    fn rho(prefix_len: u32, cycle_len: u32) -> impl Fn(&u32) -> u32 {
+        move |x| {
+            if *x + 1 == prefix_len + cycle_len {
+                prefix_len
+            } else {
+                x + 1
+            }
+        }
+    }
+
+    #[test]
+    fn detectors_agree() {
+        for prefix_len in 0..10 {
+            for cycle_len in 1..10 {
+                let expected = (prefix_len as usize, cycle_len as usize);
+                assert_eq!(floyd(0, rho(prefix_len, cycle_len)), expected);
+                assert_eq!(brent(0, rho(prefix_len, cycle_len)), expected);
+                assert_eq!(find_cycle(0, rho(prefix_len, cycle_len)), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn detects_fixed_point() {
+        assert_eq!(floyd(7, |x: &u32| *x), (0, 1));
+        assert_eq!(brent(7, |x: &u32| *x), (0, 1));
+        assert_eq!(find_cycle(7, |x: &u32| *x), (0, 1));
+    }
+
+    #[test]
+    fn detects_cycle_in_non_trivial_state() {
+        // the sequence of (x * x + 1) mod 255 starting at 3.
+        let step = |x: &u64| (x * x + 1) % 255;
+        let expected = find_cycle(3, step);
+        assert_eq!(floyd(3, step), expected);
+        assert_eq!(brent(3, step), expected);
+    }
+
+    #[test]
+    fn nth_state_matches_simulation() {
+        let step = rho(3, 4);
+        let mut state = 0;
+        for n in 0..50 {
+            assert_eq!(nth_state(0, &step, n), state);
+            state = step(&state);
+        }
+    }
+
+    #[test]
+    fn nth_state_jumps_ahead() {
+        assert_eq!(nth_state(0, rho(3, 4), 1_000_000_003), 3);
+        assert_eq!(nth_state(0, rho(3, 4), 1_000_000_004), 4);
+    }
+}
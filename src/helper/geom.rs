@@ -0,0 +1,417 @@
+//! Points and directions for grid-based puzzles.
+//!
+//! The grid convention is the one used by puzzle inputs: `x` grows to the right (columns) and `y`
+//! grows downwards (rows), so [`Direction::North`] decreases `y`.
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// Integer types that can be used as point coordinates.
+pub trait Coordinate:
+    Copy + Debug + Default + Ord + Hash + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// Returns `self + delta`, or [`None`] if the result does not fit in `Self`.
+    fn checked_offset(self, delta: isize) -> Option<Self>;
+
+    /// Returns the absolute difference between `self` and `other`.
+    fn distance(self, other: Self) -> Self;
+
+    /// Converts an [`usize`] into `Self`, or [`None`] if it does not fit.
+    fn from_usize(value: usize) -> Option<Self>;
+
+    /// Converts `self` into an [`usize`], or [`None`] if it is negative or does not fit.
+    fn to_usize(self) -> Option<usize>;
+}
+
+macro_rules! impl_coordinate {
+    ($($t:ty),*) => {
+        $(
+            impl Coordinate for $t {
+                fn checked_offset(self, delta: isize) -> Option<Self> {
+                    Self::try_from(self as i128 + delta as i128).ok()
+                }
+
+                fn distance(self, other: Self) -> Self {
+                    if self > other {
+                        self - other
+                    } else {
+                        other - self
+                    }
+                }
+
+                fn from_usize(value: usize) -> Option<Self> {
+                    Self::try_from(value).ok()
+                }
+
+                fn to_usize(self) -> Option<usize> {
+                    usize::try_from(self).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_coordinate!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// One of the 8 directions on a grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// The 4 cardinal directions, clockwise from north.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// All 8 directions, clockwise from north.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The `(dx, dy)` offset of a single step in this direction.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::NorthEast => (1, -1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (0, 1),
+            Direction::SouthWest => (-1, 1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+
+    /// Rotates the direction clockwise by `eighths` eighths of a turn (45° each).
+    /// Negative values rotate counter-clockwise.
+    pub fn rotate(self, eighths: i32) -> Direction {
+        let index = Self::ALL.iter().position(|&d| d == self).unwrap() as i32;
+        Self::ALL[(index + eighths).rem_euclid(8) as usize]
+    }
+
+    /// Quarter turn clockwise.
+    pub fn turn_right(self) -> Direction {
+        self.rotate(2)
+    }
+
+    /// Quarter turn counter-clockwise.
+    pub fn turn_left(self) -> Direction {
+        self.rotate(-2)
+    }
+
+    /// Half turn.
+    pub fn opposite(self) -> Direction {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        let (dx, dy) = self.offset();
+        dx != 0 && dy != 0
+    }
+}
+
+/// A point on a 2D grid.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Coordinate> Point2<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Converts a row-major index into a grid of the given width into a point.
+    pub fn from_index(index: usize, width: usize) -> Option<Self> {
+        Some(Self::new(
+            T::from_usize(index % width)?,
+            T::from_usize(index / width)?,
+        ))
+    }
+
+    /// Converts the point into a row-major index into a grid of the given width.
+    /// Returns [`None`] if the point lies outside of the columns of the grid.
+    pub fn to_index(self, width: usize) -> Option<usize> {
+        let x = self.x.to_usize()?;
+        let y = self.y.to_usize()?;
+        if x >= width {
+            return None;
+        }
+        y.checked_mul(width)?.checked_add(x)
+    }
+
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x.distance(other.x).max(self.y.distance(other.y))
+    }
+
+    /// Returns the point offset by `(dx, dy)`, or [`None`] if it can't be represented by `T`.
+    /// For unsigned coordinates this filters out points left of or above the origin.
+    pub fn checked_offset(self, dx: isize, dy: isize) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_offset(dx)?,
+            self.y.checked_offset(dy)?,
+        ))
+    }
+
+    /// Returns the adjacent point in the given direction, if it can be represented by `T`.
+    pub fn neighbour(self, direction: Direction) -> Option<Self> {
+        let (dx, dy) = direction.offset();
+        self.checked_offset(dx, dy)
+    }
+
+    /// Iterates over the adjacent points in each of the given directions, skipping the ones that
+    /// can't be represented by `T`.
+    ///
+    /// # Example
+    /// ```
+    /// use advent_of_code::helper::geom::{Direction, Point2};
+    ///
+    /// let corner = Point2::new(0_usize, 0);
+    /// assert_eq!(corner.neighbours(Direction::ALL).count(), 3);
+    /// ```
+    pub fn neighbours<I>(self, directions: I) -> impl Iterator<Item = Self>
+    where
+        I: IntoIterator<Item = Direction>,
+    {
+        directions
+            .into_iter()
+            .filter_map(move |direction| self.neighbour(direction))
+    }
+}
+
+impl<T: Coordinate> Add for Point2<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Coordinate> Sub for Point2<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Point2<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Coordinate + Neg<Output = T>> Neg for Point2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Point2<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Coordinate> SubAssign for Point2<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Coordinate> From<(T, T)> for Point2<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+/// A point in 3D space.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point3<T> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+}
+
+impl<T: Coordinate> Point3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Self) -> T {
+        self.x.distance(other.x) + self.y.distance(other.y) + self.z.distance(other.z)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        self.x
+            .distance(other.x)
+            .max(self.y.distance(other.y))
+            .max(self.z.distance(other.z))
+    }
+
+    /// Returns the point offset by `(dx, dy, dz)`, or [`None`] if it can't be represented by `T`.
+    pub fn checked_offset(self, dx: isize, dy: isize, dz: isize) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_offset(dx)?,
+            self.y.checked_offset(dy)?,
+            self.z.checked_offset(dz)?,
+        ))
+    }
+}
+
+impl<T: Coordinate> Add for Point3<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl<T: Coordinate> Sub for Point3<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<T: Coordinate> Mul<T> for Point3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl<T: Coordinate + Neg<Output = T>> Neg for Point3<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Coordinate> AddAssign for Point3<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Coordinate> SubAssign for Point3<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Coordinate> From<(T, T, T)> for Point3<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Direction, Point2, Point3};
+
+    #[test]
+    fn point2_operators() {
+        let a = Point2::new(1, 2);
+        let b = Point2::new(-3, 5);
+        assert_eq!(a + b, Point2::new(-2, 7));
+        assert_eq!(a - b, Point2::new(4, -3));
+        assert_eq!(a * 3, Point2::new(3, 6));
+        assert_eq!(-a, Point2::new(-1, -2));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+    }
+
+    #[test]
+    fn point_distances() {
+        let a = Point2::new(1_u32, 7);
+        let b = Point2::new(4_u32, 2);
+        assert_eq!(a.manhattan(b), 8);
+        assert_eq!(a.chebyshev(b), 5);
+
+        let a = Point3::new(-1_i64, 0, 3);
+        let b = Point3::new(2_i64, -4, 3);
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+    }
+
+    #[test]
+    fn direction_turns() {
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite().opposite(), direction);
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_right().turn_right(), direction.opposite());
+            let (dx, dy) = direction.offset();
+            assert_eq!(direction.opposite().offset(), (-dx, -dy));
+        }
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::West.turn_right(), Direction::North);
+        assert_eq!(Direction::NorthEast.turn_left(), Direction::NorthWest);
+        assert_eq!(Direction::North.rotate(-1), Direction::NorthWest);
+        assert_eq!(
+            Direction::CARDINAL
+                .iter()
+                .filter(|d| d.is_diagonal())
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn neighbours_respect_unsigned_bounds() {
+        let origin = Point2::new(0_usize, 0);
+        assert_eq!(
+            origin.neighbours(Direction::CARDINAL).collect::<Vec<_>>(),
+            vec![Point2::new(1, 0), Point2::new(0, 1)]
+        );
+        assert_eq!(Point2::new(1_i32, 1).neighbours(Direction::ALL).count(), 8);
+        assert_eq!(Point2::new(255_u8, 0).neighbour(Direction::East), None);
+    }
+
+    #[test]
+    fn grid_index_conversion() {
+        let width = 7;
+        for index in 0..50 {
+            let point = Point2::<i32>::from_index(index, width).unwrap();
+            assert_eq!(point.to_index(width), Some(index));
+        }
+        assert_eq!(Point2::new(3_u8, 2).to_index(10), Some(23));
+        assert_eq!(Point2::new(10_u8, 2).to_index(10), None);
+        assert_eq!(Point2::new(-1_i32, 2).to_index(10), None);
+        assert_eq!(Point2::<u8>::from_index(2560, 10), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Export of directed graphs to the [DOT](https://graphviz.org/doc/info/lang.html) format, to look
+//! at puzzle inputs with Graphviz.
+//!
+//! Solutions call [`dump_requested`] and [`write_dot`] to write graphs next to their input when
+//! `cargo solve` is called with `--dump-dot`.
+//!
+//! # Example
+//! ```
+//! use advent_of_code::helper::graph::Graph;
+//!
+//! let mut graph = Graph::new("network");
+//! graph
+//!     .node("AAA", &[("style", "filled")])
+//!     .edge("AAA", "BBB", Some("L"))
+//!     .edge("BBB", "AAA", None);
+//! assert_eq!(
+//!     graph.to_string(),
+//!     "digraph \"network\" {\n  \"AAA\" [style=\"filled\"];\n  \"BBB\";\n  \"AAA\" -> \"BBB\" [label=\"L\"];\n  \"BBB\" -> \"AAA\";\n}\n"
+//! );
+//! ```
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+use crate::template::{flags, Day};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Edge {
+    from: usize,
+    to: usize,
+    label: Option<String>,
+}
+
+/// A directed graph with optional node attributes and edge labels.
+/// Nodes are written in the order they were first mentioned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    name: String,
+    ids: HashMap<String, usize>,
+    nodes: Vec<(String, Vec<(String, String)>)>,
+    edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ids: HashMap::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    fn index(&mut self, id: &str) -> usize {
+        if let Some(&index) = self.ids.get(id) {
+            return index;
+        }
+        self.nodes.push((id.to_string(), Vec::new()));
+        self.ids.insert(id.to_string(), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    /// Adds a node, or adds `attributes` to an existing one.
+    pub fn node(&mut self, id: &str, attributes: &[(&str, &str)]) -> &mut Self {
+        let index = self.index(id);
+        self.nodes[index].1.extend(
+            attributes
+                .iter()
+                .map(|&(key, value)| (key.to_string(), value.to_string())),
+        );
+        self
+    }
+
+    /// Adds an edge, adding its nodes if needed.
+    pub fn edge(&mut self, from: &str, to: &str, label: Option<&str>) -> &mut Self {
+        let edge = Edge {
+            from: self.index(from),
+            to: self.index(to),
+            label: label.map(str::to_string),
+        };
+        self.edges.push(edge);
+        self
+    }
+
+    /// The sub-graph of the nodes reachable from `roots` (roots included), with the edges between
+    /// them. Unknown roots are ignored.
+    pub fn reachable_from(&self, name: &str, roots: &[&str]) -> Graph {
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        for edge in self.edges.iter() {
+            successors[edge.from].push(edge.to);
+        }
+
+        let mut reachable = HashSet::new();
+        let mut queue = roots
+            .iter()
+            .filter_map(|root| self.ids.get(*root).copied())
+            .collect::<Vec<_>>();
+        while let Some(index) = queue.pop() {
+            if reachable.insert(index) {
+                queue.extend(successors[index].iter().copied());
+            }
+        }
+
+        let mut graph = Graph::new(name);
+        for (index, (id, attributes)) in self.nodes.iter().enumerate() {
+            if reachable.contains(&index) {
+                graph.index(id);
+                graph.nodes.last_mut().unwrap().1 = attributes.clone();
+            }
+        }
+        for edge in self
+            .edges
+            .iter()
+            .filter(|edge| reachable.contains(&edge.from))
+        {
+            let (from, to) = (&self.nodes[edge.from].0, &self.nodes[edge.to].0);
+            graph.edge(from, to, edge.label.as_deref());
+        }
+        graph
+    }
+}
+
+/// Quotes an identifier, escaping quotes and backslashes.
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl Display for Graph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "digraph {} {{", quote(&self.name))?;
+        for (id, attributes) in self.nodes.iter() {
+            write!(f, "  {}", quote(id))?;
+            if !attributes.is_empty() {
+                let attributes = attributes
+                    .iter()
+                    .map(|(key, value)| format!("{key}={}", quote(value)))
+                    .collect::<Vec<_>>();
+                write!(f, " [{}]", attributes.join(", "))?;
+            }
+            writeln!(f, ";")?;
+        }
+        for edge in self.edges.iter() {
+            let (from, to) = (&self.nodes[edge.from].0, &self.nodes[edge.to].0);
+            write!(f, "  {} -> {}", quote(from), quote(to))?;
+            if let Some(label) = &edge.label {
+                write!(f, " [label={}]", quote(label))?;
+            }
+            writeln!(f, ";")?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+/// Whether the solution was called with `--dump-dot`. Always false when benchmarking with `--time`.
+pub fn dump_requested() -> bool {
+    flags::is_set("--dump-dot")
+}
+
+/// Writes `graph` next to the input of `day`, as `data/inputs/<day>.dot`, or
+/// `data/inputs/<day>-<suffix>.dot` if a suffix is given. Returns the path of the written file.
+pub fn write_dot(day: Day, suffix: Option<&str>, graph: &Graph) -> io::Result<PathBuf> {
+    let file_name = match suffix {
+        Some(suffix) => format!("{day}-{suffix}.dot"),
+        None => format!("{day}.dot"),
+    };
+    let path = env::current_dir()?
+        .join("data")
+        .join("inputs")
+        .join(file_name);
+    fs::write(&path, graph.to_string())?;
+    Ok(path)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Graph;
+
+    #[test]
+    fn renders_attributes_and_labels() {
+        let mut graph = Graph::new("g");
+        graph
+            .edge("a", "b", Some("L"))
+            .node("a", &[("color", "red")])
+            .node("a", &[("shape", "box")])
+            .node("quote\"d", &[]);
+        assert_eq!(
+            graph.to_string(),
+            concat!(
+                "digraph \"g\" {\n",
+                "  \"a\" [color=\"red\", shape=\"box\"];\n",
+                "  \"b\";\n",
+                "  \"quote\\\"d\";\n",
+                "  \"a\" -> \"b\" [label=\"L\"];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn keeps_reachable_nodes() {
+        let mut graph = Graph::new("g");
+        graph
+            .node("a", &[("color", "red")])
+            .edge("a", "b", None)
+            .edge("b", "c", None)
+            .edge("c", "b", None)
+            .edge("d", "a", None)
+            .edge("e", "e", None);
+
+        let reachable = graph.reachable_from("from b", &["b", "unknown"]);
+        let mut expected = Graph::new("from b");
+        expected.edge("b", "c", None).edge("c", "b", None);
+        assert_eq!(reachable, expected);
+
+        let reachable = graph.reachable_from("from a", &["a"]);
+        assert_eq!(
+            reachable.to_string(),
+            "digraph \"from a\" {\n  \"a\" [color=\"red\"];\n  \"b\";\n  \"c\";\n  \"a\" -> \"b\";\n  \"b\" -> \"c\";\n  \"c\" -> \"b\";\n}\n"
+        );
+    }
+}
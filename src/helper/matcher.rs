@@ -0,0 +1,209 @@
+//! Search for many patterns at once, in a single pass over the input.
+//!
+//! [`Matcher`] is an [Aho-Corasick](https://en.wikipedia.org/wiki/Aho%E2%80%93Corasick_algorithm)
+//! automaton: it reports every occurrence of every pattern, including occurrences that overlap,
+//! like both `one` and `eight` in `oneight`.
+//!
+//! # Example
+//! ```
+//! use advent_of_code::helper::matcher::Matcher;
+//!
+//! let matcher = Matcher::new(["one", "eight", "nine"]);
+//! let found: Vec<_> = matcher.find_iter("oneightnine").map(|m| m.pattern).collect();
+//! assert_eq!(found, vec![0, 1, 2]);
+//! assert_eq!(matcher.first("oneightnine").unwrap().start, 0);
+//! assert_eq!(matcher.last("oneightnine").unwrap().start, 7);
+//! ```
+use std::collections::VecDeque;
+
+/// An occurrence of a pattern: the index of the pattern and its byte range in the haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A set of patterns compiled into an automaton. Patterns are matched byte by byte, empty
+/// patterns never match.
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    /// The state reached from each state on each byte, failure links already followed.
+    transitions: Vec<[usize; 256]>,
+    /// The patterns ending at each state, including the ones reached through failure links.
+    outputs: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+impl Matcher {
+    pub fn new<P: AsRef<[u8]>>(patterns: impl IntoIterator<Item = P>) -> Self {
+        let mut transitions = vec![[0_usize; 256]];
+        let mut outputs = vec![Vec::new()];
+        let mut lengths = Vec::new();
+        // build the trie first, where 0 means "no child": the root can't be a child.
+        for (index, pattern) in patterns.into_iter().enumerate() {
+            let pattern = pattern.as_ref();
+            lengths.push(pattern.len());
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = 0;
+            for &byte in pattern {
+                if transitions[state][byte as usize] == 0 {
+                    transitions.push([0; 256]);
+                    outputs.push(Vec::new());
+                    transitions[state][byte as usize] = transitions.len() - 1;
+                }
+                state = transitions[state][byte as usize];
+            }
+            outputs[state].push(index);
+        }
+
+        // breadth first, so that the failure link of each state is done before its children.
+        let mut failure = vec![0; transitions.len()];
+        let mut queue =
+            VecDeque::from_iter(transitions[0].iter().copied().filter(|&child| child != 0));
+        while let Some(state) = queue.pop_front() {
+            let fallback = failure[state];
+            let inherited = outputs[fallback].clone();
+            outputs[state].extend(inherited);
+            let fallback_transitions = transitions[fallback];
+            for (child, fallback_child) in transitions[state].iter_mut().zip(fallback_transitions) {
+                if *child == 0 {
+                    *child = fallback_child;
+                } else {
+                    failure[*child] = fallback_child;
+                    queue.push_back(*child);
+                }
+            }
+        }
+
+        Self {
+            transitions,
+            outputs,
+            lengths,
+        }
+    }
+
+    /// All occurrences of all patterns, overlaps included, ordered by their end.
+    /// Occurrences ending at the same byte are ordered from longest to shortest.
+    pub fn find_iter<'a>(&'a self, haystack: &'a str) -> impl Iterator<Item = Match> + 'a {
+        haystack
+            .bytes()
+            .enumerate()
+            .scan(0, |state, (index, byte)| {
+                *state = self.transitions[*state][byte as usize];
+                Some((index + 1, *state))
+            })
+            .flat_map(move |(end, state)| {
+                self.outputs[state].iter().map(move |&pattern| Match {
+                    pattern,
+                    start: end - self.lengths[pattern],
+                    end,
+                })
+            })
+    }
+
+    /// The occurrence starting first, the longest one if several start at the same byte.
+    pub fn first(&self, haystack: &str) -> Option<Match> {
+        let longest = self.lengths.iter().copied().max().unwrap_or(0);
+        let mut best: Option<Match> = None;
+        for found in self.find_iter(haystack) {
+            if best.is_some_and(|best| found.end > best.start + longest) {
+                // nothing found from now on can start earlier.
+                break;
+            }
+            // occurrences come by increasing end: a later one with the same start is longer.
+            if best.is_none_or(|best| found.start <= best.start) {
+                best = Some(found);
+            }
+        }
+        best
+    }
+
+    /// The occurrence starting last, the longest one if several start at the same byte.
+    pub fn last(&self, haystack: &str) -> Option<Match> {
+        self.find_iter(haystack)
+            .max_by_key(|found| (found.start, found.end))
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Match, Matcher};
+
+    const DIGITS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    fn found(matcher: &Matcher, haystack: &str) -> Vec<(usize, usize)> {
+        matcher
+            .find_iter(haystack)
+            .map(|found| (found.pattern, found.start))
+            .collect()
+    }
+
+    #[test]
+    fn finds_overlapping_words() {
+        let matcher = Matcher::new(DIGITS);
+        assert_eq!(found(&matcher, "oneight"), vec![(0, 0), (7, 2)]);
+        assert_eq!(found(&matcher, "sevenine"), vec![(6, 0), (8, 4)]);
+        assert_eq!(found(&matcher, "twone"), vec![(1, 0), (0, 2)]);
+        assert_eq!(
+            found(&matcher, "eightwothree"),
+            vec![(7, 0), (1, 4), (2, 7)]
+        );
+        assert_eq!(found(&matcher, "nineight"), vec![(8, 0), (7, 3)]);
+        assert_eq!(found(&matcher, "xtwthreex"), vec![(2, 3)]);
+        assert_eq!(found(&matcher, "ninine"), vec![(8, 2)]);
+    }
+
+    #[test]
+    fn finds_nested_patterns() {
+        let matcher = Matcher::new(["he", "she", "his", "hers", "e"]);
+        assert_eq!(
+            found(&matcher, "ushers"),
+            vec![(1, 1), (0, 2), (4, 3), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn finds_first_and_last() {
+        let matcher = Matcher::new(DIGITS);
+        assert_eq!(
+            matcher.first("xoneightx"),
+            Some(Match {
+                pattern: 0,
+                start: 1,
+                end: 4
+            })
+        );
+        assert_eq!(
+            matcher.last("xoneightx"),
+            Some(Match {
+                pattern: 7,
+                start: 3,
+                end: 8
+            })
+        );
+        assert_eq!(matcher.first("nothing here"), None);
+        assert_eq!(matcher.last(""), None);
+
+        // a long pattern starting first is found after a short one ending first.
+        let matcher = Matcher::new(["abcd", "bc", "d"]);
+        assert_eq!(matcher.first("abcd").map(|found| found.pattern), Some(0));
+        assert_eq!(matcher.last("abcd").map(|found| found.pattern), Some(2));
+
+        // the longest pattern wins between patterns starting at the same byte.
+        let matcher = Matcher::new(["ab", "abc", "a"]);
+        assert_eq!(matcher.first("abc").map(|found| found.pattern), Some(1));
+        assert_eq!(matcher.last("xabc").map(|found| found.pattern), Some(1));
+    }
+
+    #[test]
+    fn ignores_empty_patterns() {
+        let matcher = Matcher::new(["", "a", "a"]);
+        assert_eq!(found(&matcher, "aa"), vec![(1, 0), (2, 0), (1, 1), (2, 1)]);
+        assert_eq!(Matcher::new([""; 0]).first("abc"), None);
+    }
+}
@@ -0,0 +1,289 @@
+//! Integer math helpers.
+use std::fmt::Display;
+
+/// Returns the integer square root of `n`, i.e. the largest `r` such that `r * r <= n`.
+///
+/// # Example
+/// ```
+/// use advent_of_code::helper::math::isqrt;
+///
+/// assert_eq!(isqrt(99), 9);
+/// assert_eq!(isqrt(100), 10);
+/// ```
+pub fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // start from a power of two that is at least sqrt(n), Newton's method then decreases
+    // monotonically towards the root.
+    let bits = 128 - n.leading_zeros();
+    let mut x = 1_u128 << bits.div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Greatest common divisor.
+pub fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Least common multiple, or [`None`] if it doesn't fit in an [`u128`].
+pub fn lcm(a: u128, b: u128) -> Option<u128> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Computes `a * b % m` without overflowing.
+fn mul_mod(a: u128, b: u128, m: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product % m;
+    }
+    let (mut a, mut b, mut result) = (a % m, b % m, 0_u128);
+    while b > 0 {
+        if b & 1 == 1 {
+            result = add_mod(result, a, m);
+        }
+        a = add_mod(a, a, m);
+        b >>= 1;
+    }
+    result
+}
+
+/// Computes `(a + b) % m` for `a, b < m` without overflowing.
+fn add_mod(a: u128, b: u128, m: u128) -> u128 {
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// Modular inverse of `a` modulo `m`, for `a` and `m` coprime.
+fn inverse_mod(a: u128, m: u128) -> u128 {
+    // extended euclid, keeping the coefficients of `a` reduced modulo `m`.
+    let (mut old_r, mut r) = (a % m, m);
+    let (mut old_s, mut s) = (1 % m, 0_u128);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        let product = mul_mod(quotient, s, m);
+        (old_s, s) = (s, (old_s + m - product) % m);
+    }
+    old_s
+}
+
+/// Generalized chinese remainder theorem: combines `x ≡ a (mod m)` and `x ≡ b (mod n)` into a
+/// single congruence `x ≡ r (mod lcm(m, n))`, returned as `(r, lcm(m, n))`.
+/// The moduli don't need to be coprime.
+///
+/// Returns [`None`] if the congruences have no common solution, or if `lcm(m, n)` doesn't fit in
+/// an [`u128`].
+///
+/// # Example
+/// ```
+/// use advent_of_code::helper::math::crt;
+///
+/// // x ≡ 2 (mod 4) and x ≡ 4 (mod 6) means x ≡ 10 (mod 12).
+/// assert_eq!(crt(2, 4, 4, 6), Some((10, 12)));
+/// assert_eq!(crt(1, 4, 2, 6), None);
+/// ```
+pub fn crt(a: u128, m: u128, b: u128, n: u128) -> Option<(u128, u128)> {
+    let (a, b) = (a % m, b % n);
+    let g = gcd(m, n);
+    let difference = if b >= a { b - a } else { n - (a - b) % n };
+    if difference % g != 0 {
+        return None;
+    }
+    let modulus = lcm(m, n)?;
+    // x = a + m * k, with m * k ≡ b - a (mod n), i.e. (m / g) * k ≡ (b - a) / g (mod n / g).
+    let reduced = n / g;
+    let k = mul_mod(difference / g, inverse_mod(m / g, reduced), reduced);
+    let offset = mul_mod(m, k, modulus);
+    Some((add_mod(a, offset, modulus), modulus))
+}
+
+/// An arithmetic operation overflowed its integer type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl Display for Overflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "arithmetic overflow")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// Integers with checked addition and multiplication, for [`CheckedIterator`].
+pub trait CheckedInt: Copy {
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+macro_rules! impl_checked_int {
+    ($($int:ty),*) => {
+        $(
+            impl CheckedInt for $int {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$int>::checked_add(self, other)
+                }
+
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$int>::checked_mul(self, other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_int!(u32, u64, u128, usize, i32, i64, i128);
+
+/// Sums and products failing with [`Overflow`] instead of wrapping around.
+///
+/// # Example
+/// ```
+/// use advent_of_code::helper::math::{CheckedIterator, Overflow};
+///
+/// assert_eq!([1_u64, 2, 3].into_iter().checked_sum(), Ok(6));
+/// assert_eq!([u64::MAX, 1].into_iter().checked_sum(), Err(Overflow));
+/// assert_eq!([1_u32 << 16, 1 << 16].into_iter().checked_product(), Err(Overflow));
+/// ```
+pub trait CheckedIterator: Iterator {
+    fn checked_sum(mut self) -> Result<Self::Item, Overflow>
+    where
+        Self: Sized,
+        Self::Item: CheckedInt,
+    {
+        self.try_fold(Self::Item::ZERO, |sum, value| {
+            sum.checked_add(value).ok_or(Overflow)
+        })
+    }
+
+    fn checked_product(mut self) -> Result<Self::Item, Overflow>
+    where
+        Self: Sized,
+        Self::Item: CheckedInt,
+    {
+        self.try_fold(Self::Item::ONE, |product, value| {
+            product.checked_mul(value).ok_or(Overflow)
+        })
+    }
+}
+
+impl<I: Iterator> CheckedIterator for I {}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{crt, gcd, isqrt, lcm, mul_mod, CheckedIterator, Overflow};
+
+    #[test]
+    fn isqrt_small_values() {
+        for n in 0..10_000_u128 {
+            let r = isqrt(n);
+            assert!(r * r <= n, "isqrt({n}) = {r} is too large");
+            assert!((r + 1) * (r + 1) > n, "isqrt({n}) = {r} is too small");
+        }
+    }
+
+    #[test]
+    fn isqrt_perfect_squares() {
+        for r in [
+            1_u128,
+            2,
+            3,
+            1 << 20,
+            1 << 40,
+            (1 << 63) - 1,
+            u64::MAX as u128,
+        ] {
+            assert_eq!(isqrt(r * r), r);
+            assert_eq!(isqrt(r * r - 1), r - 1);
+            assert_eq!(isqrt(r * r + 1), r);
+        }
+    }
+
+    #[test]
+    fn isqrt_max() {
+        assert_eq!(isqrt(u128::MAX), u64::MAX as u128);
+    }
+
+    #[test]
+    fn gcd_lcm() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(0, 5), 5);
+        assert_eq!(lcm(4, 6), Some(12));
+        assert_eq!(lcm(0, 6), Some(0));
+        assert_eq!(lcm(u128::MAX, 2), None);
+    }
+
+    #[test]
+    fn crt_matches_brute_force() {
+        for m in 1..13_u128 {
+            for n in 1..13_u128 {
+                for a in 0..m {
+                    for b in 0..n {
+                        let expected = (0..m * n).find(|x| x % m == a && x % n == b);
+                        match crt(a, m, b, n) {
+                            Some((r, modulus)) => {
+                                assert_eq!(modulus, lcm(m, n).unwrap());
+                                assert_eq!(Some(r), expected, "{a} mod {m}, {b} mod {n}");
+                            }
+                            None => assert_eq!(expected, None, "{a} mod {m}, {b} mod {n}"),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn crt_large_moduli() {
+        let m = (1_u128 << 89) - 1;
+        let n = (1_u128 << 31) - 1;
+        let (r, modulus) = crt(5, m, 7, n).unwrap();
+        assert_eq!(modulus, m * n);
+        assert_eq!(r % m, 5);
+        assert_eq!(r % n, 7);
+    }
+
+    #[test]
+    fn checked_sum_and_product() {
+        assert_eq!(std::iter::empty::<u64>().checked_sum(), Ok(0));
+        assert_eq!(std::iter::empty::<u64>().checked_product(), Ok(1));
+        assert_eq!([u32::MAX - 1, 1].into_iter().checked_sum(), Ok(u32::MAX));
+        assert_eq!([u32::MAX - 1, 2].into_iter().checked_sum(), Err(Overflow));
+        assert_eq!([-5_i64, 3].into_iter().checked_sum(), Ok(-2));
+        assert_eq!([i64::MIN, -1].into_iter().checked_sum(), Err(Overflow));
+        assert_eq!(
+            [1_u64 << 32, 1 << 31].into_iter().checked_product(),
+            Ok(1 << 63)
+        );
+        assert_eq!(
+            [1_u64 << 32, 1 << 32, 0].into_iter().checked_product(),
+            Err(Overflow)
+        );
+    }
+
+    #[test]
+    fn mul_mod_does_not_overflow() {
+        let m = (1_u128 << 127) - 1;
+        assert_eq!(mul_mod(m - 1, m - 1, m), 1);
+        assert_eq!(mul_mod(1 << 100, 1 << 100, m), 1 << 73);
+    }
+}
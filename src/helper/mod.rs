@@ -0,0 +1,12 @@
+pub mod cycle;
+pub mod geom;
+pub mod graph;
+pub mod matcher;
+pub mod math;
+mod parse_error;
+pub mod parser;
+pub mod property;
+pub mod trace;
+
+pub use parse_error::ParseError;
+pub use trace::{debug_item, trace_item};
@@ -0,0 +1,119 @@
+//! The error returned when a puzzle input is malformed.
+//!
+//! Errors created by a [`Parser`](super::parser::Parser) point at the offending token and print
+//! the line they were found on:
+//!
+//! ```text
+//! line 3, column 8: expected `:`, found `5`
+//!   3 | Game 3 5 green
+//!     |        ^
+//! ```
+//!
+//! `Debug` prints the same, so that days unwrapping their parse results panic with it.
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::num::ParseIntError;
+
+/// Lines and columns are 1-based, columns are counted in characters. Both are 0 when the position
+/// is unknown, like for errors converted from a [`ParseIntError`].
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+    pub found: String,
+    /// The input line the error was found on, empty if unknown.
+    pub snippet: String,
+}
+
+impl Error for ParseError {}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.line > 0 {
+            write!(f, "line {}, column {}: ", self.line, self.column)?;
+        }
+        write!(f, "expected {}, found {}", self.expected, self.found)?;
+        if self.snippet.is_empty() {
+            return Ok(());
+        }
+        let gutter = self.line.to_string();
+        // tabs are kept so that the caret lines up with the snippet.
+        let indent: String = self
+            .snippet
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(
+            f,
+            "\n  {gutter} | {}\n  {} | {indent}^",
+            self.snippet,
+            " ".repeat(gutter.len())
+        )
+    }
+}
+
+impl Debug for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl From<ParseIntError> for ParseError {
+    fn from(error: ParseIntError) -> Self {
+        ParseError {
+            line: 0,
+            column: 0,
+            expected: "a number".to_string(),
+            found: format!("an invalid number ({error})"),
+            snippet: String::new(),
+        }
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::ParseError;
+    use crate::helper::parser::Parser;
+
+    #[test]
+    fn displays_snippet() {
+        let mut parser = Parser::new("Game 1: 2\nGame 3 5 green");
+        let mut line = parser.lines().nth(1).unwrap();
+        let error = line.key_value(
+            ":",
+            |p| {
+                p.literal("Game")?;
+                p.number::<u8>()
+            },
+            |p| p.number::<u8>(),
+        );
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "line 2, column 8: expected `:`, found `5`\n  2 | Game 3 5 green\n    |        ^"
+        );
+
+        parser = Parser::new("\ta\tb");
+        let error = parser.number::<u8>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 2: expected a number, found `a`\n  1 | \ta\tb\n    | \t^"
+        );
+        assert_eq!(format!("{error:?}"), error.to_string());
+    }
+
+    #[test]
+    fn converts_parse_int_errors() {
+        fn parse(s: &str) -> Result<u8, ParseError> {
+            Ok(s.parse::<u8>()?)
+        }
+        assert_eq!(parse("12"), Ok(12));
+        let error = parse("300").unwrap_err();
+        assert_eq!((error.line, error.column), (0, 0));
+        assert_eq!(
+            error.to_string(),
+            "expected a number, found an invalid number (number too large to fit in target type)"
+        );
+    }
+}
@@ -0,0 +1,392 @@
+//! A small toolkit to parse puzzle inputs, reporting where parsing failed.
+//!
+//! A [`Parser`] is a cursor over (a slice of) the input. Token parsers like [`Parser::literal`]
+//! and [`Parser::number`] skip spaces and tabs before the token, but never newlines: inputs are
+//! split into [`Parser::lines`] or [`Parser::sections`] first, which keep track of line numbers.
+//! Token parsers don't consume any input when they fail.
+//!
+//! # Example
+//! ```
+//! use advent_of_code::helper::parser::Parser;
+//!
+//! let mut parser = Parser::new("Game 12: 3 blue, 4 red");
+//! let (id, cubes) = parser
+//!     .key_value(
+//!         ":",
+//!         |p| {
+//!             p.literal("Game")?;
+//!             p.number::<u32>()
+//!         },
+//!         |p| p.separated(",", |p| Ok((p.number::<u32>()?, p.word()?))),
+//!     )
+//!     .unwrap();
+//! assert_eq!(id, 12);
+//! assert_eq!(cubes, vec![(3, "blue"), (4, "red")]);
+//! ```
+use std::str::FromStr;
+
+use super::ParseError;
+
+/// A cursor over an input string.
+#[derive(Debug, Clone, Copy)]
+pub struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+    first_line: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            offset: 0,
+            first_line: 1,
+        }
+    }
+
+    /// The unparsed part of the input.
+    pub fn rest(&self) -> &'a str {
+        &self.source[self.offset..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    /// The 1-based line of the current position.
+    pub fn line(&self) -> usize {
+        self.first_line + self.source[..self.offset].matches('\n').count()
+    }
+
+    /// The 1-based column of the current position.
+    pub fn column(&self) -> usize {
+        let consumed = &self.source[..self.offset];
+        let line_start = consumed.rfind('\n').map_or(0, |i| i + 1);
+        consumed[line_start..].chars().count() + 1
+    }
+
+    /// The whole input line of the current position.
+    fn current_line(&self) -> &'a str {
+        let line_start = self.source[..self.offset].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = self
+            .rest()
+            .find('\n')
+            .map_or(self.source.len(), |i| self.offset + i);
+        self.source[line_start..line_end].trim_end_matches('\r')
+    }
+
+    /// Creates an error at the current position.
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        let found = match self.rest().chars().next() {
+            None | Some('\n') => "end of line".to_string(),
+            Some(_) => {
+                let token = self
+                    .rest()
+                    .split(char::is_whitespace)
+                    .next()
+                    .unwrap_or_default();
+                format!("`{token}`")
+            }
+        };
+        ParseError {
+            line: self.line(),
+            column: self.column(),
+            expected: expected.into(),
+            found,
+            snippet: self.current_line().to_string(),
+        }
+    }
+
+    /// Skips spaces and tabs.
+    pub fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.offset += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    }
+
+    /// Consumes the longest prefix whose characters all match `predicate`. May be empty.
+    pub fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest
+            .char_indices()
+            .find(|&(_, c)| !predicate(c))
+            .map_or(rest.len(), |(i, _)| i);
+        self.offset += len;
+        &rest[..len]
+    }
+
+    /// Consumes a single character, without skipping spaces.
+    pub fn next_char(&mut self) -> Option<char> {
+        let c = self.rest().chars().next()?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consumes `literal` if the input continues with it, returns whether it did.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        let mut attempt = *self;
+        attempt.skip_spaces();
+        if attempt.rest().starts_with(literal) {
+            attempt.offset += literal.len();
+            *self = attempt;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Consumes `literal`, or fails if the input doesn't continue with it.
+    pub fn literal(&mut self, literal: &str) -> Result<(), ParseError> {
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.token_start().error(format!("`{literal}`")))
+        }
+    }
+
+    /// Consumes a non-empty run of alphanumeric characters.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        let mut attempt = self.token_start();
+        match attempt.take_while(char::is_alphanumeric) {
+            "" => Err(self.token_start().error("a word")),
+            word => {
+                *self = attempt;
+                Ok(word)
+            }
+        }
+    }
+
+    /// Consumes an integer, with an optional leading `-`.
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let start = self.token_start();
+        let mut attempt = start;
+        let sign = usize::from(attempt.rest().starts_with('-'));
+        attempt.offset += sign;
+        let digits = attempt.take_while(|c| c.is_ascii_digit());
+        if digits.is_empty() {
+            return Err(start.error("a number"));
+        }
+        let number = start.rest()[..sign + digits.len()].parse().map_err(|_| {
+            start.error(format!(
+                "a number fitting in `{}`",
+                std::any::type_name::<T>()
+            ))
+        })?;
+        *self = attempt;
+        Ok(number)
+    }
+
+    /// A copy of the parser positioned at the start of the next token.
+    fn token_start(&self) -> Self {
+        let mut start = *self;
+        start.skip_spaces();
+        start
+    }
+
+    /// Fails unless all of the input has been consumed, trailing spaces excepted.
+    pub fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_spaces();
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+
+    /// Parses `item` as many times as possible.
+    ///
+    /// Stops at the first item that fails (or succeeds) without consuming any input. An item that
+    /// fails after consuming input is a malformed item rather than the end of the list, so its
+    /// error is returned.
+    pub fn many<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            let checkpoint = *self;
+            match item(self) {
+                Ok(value) if self.offset > checkpoint.offset => items.push(value),
+                Err(error) if self.offset > checkpoint.offset => return Err(error),
+                _ => {
+                    *self = checkpoint;
+                    return Ok(items);
+                }
+            }
+        }
+    }
+
+    /// Parses a non-empty list of `item`s separated by `separator`.
+    pub fn separated<T>(
+        &mut self,
+        separator: &str,
+        mut item: impl FnMut(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<Vec<T>, ParseError> {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// Parses `<key> <separator> <value>`.
+    pub fn key_value<K, V>(
+        &mut self,
+        separator: &str,
+        key: impl FnOnce(&mut Self) -> Result<K, ParseError>,
+        value: impl FnOnce(&mut Self) -> Result<V, ParseError>,
+    ) -> Result<(K, V), ParseError> {
+        let key = key(self)?;
+        self.literal(separator)?;
+        let value = value(self)?;
+        Ok((key, value))
+    }
+
+    /// Splits the rest of the input into one parser per line.
+    pub fn lines(&self) -> impl Iterator<Item = Parser<'a>> {
+        let first_line = self.line();
+        self.rest()
+            .lines()
+            .enumerate()
+            .map(move |(index, line)| Parser {
+                source: line,
+                offset: 0,
+                first_line: first_line + index,
+            })
+    }
+
+    /// Splits the rest of the input into one parser per group of lines separated by blank lines.
+    pub fn sections(&self) -> Vec<Parser<'a>> {
+        let rest = self.rest();
+        let first_line = self.line();
+        let mut sections = Vec::new();
+        // byte offset and line number of the start of the current section.
+        let mut current: Option<(usize, usize)> = None;
+        let mut offset = 0;
+        let mut section_end = 0;
+
+        for (index, line) in rest.split_inclusive('\n').enumerate() {
+            if line.trim().is_empty() {
+                if let Some((start, line_number)) = current.take() {
+                    sections.push(Parser {
+                        source: &rest[start..section_end],
+                        offset: 0,
+                        first_line: line_number,
+                    });
+                }
+            } else {
+                current.get_or_insert((offset, first_line + index));
+                section_end = offset + line.trim_end_matches(['\n', '\r']).len();
+            }
+            offset += line.len();
+        }
+
+        if let Some((start, line_number)) = current {
+            sections.push(Parser {
+                source: &rest[start..section_end],
+                offset: 0,
+                first_line: line_number,
+            });
+        }
+
+        sections
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{ParseError, Parser};
+
+    #[test]
+    fn parses_tokens() {
+        let mut parser = Parser::new("  move -12 to  crate7");
+        parser.literal("move").unwrap();
+        assert_eq!(parser.number::<i32>(), Ok(-12));
+        assert!(!parser.eat("from"));
+        assert!(parser.eat("to"));
+        assert_eq!(parser.word(), Ok("crate7"));
+        assert_eq!(parser.end(), Ok(()));
+    }
+
+    #[test]
+    fn parses_characters() {
+        let mut parser = Parser::new(" é.");
+        assert_eq!(parser.next_char(), Some(' '));
+        assert_eq!(parser.next_char(), Some('é'));
+        assert_eq!(parser.column(), 3);
+        assert_eq!(parser.next_char(), Some('.'));
+        assert_eq!(parser.next_char(), None);
+    }
+
+    #[test]
+    fn parses_lists() {
+        let mut parser = Parser::new("1, 2,3 | 4 5 6");
+        assert_eq!(
+            parser.separated(",", |p| p.number::<u8>()),
+            Ok(vec![1, 2, 3])
+        );
+        parser.literal("|").unwrap();
+        assert_eq!(parser.many(|p| p.number::<u8>()), Ok(vec![4, 5, 6]));
+        assert!(parser.end().is_ok());
+    }
+
+    #[test]
+    fn reports_position_of_errors() {
+        let mut parser = Parser::new("seeds: 1 2 x");
+        parser.literal("seeds:").unwrap();
+        parser.many(|p| p.number::<u64>()).unwrap();
+        assert_eq!(
+            parser.end(),
+            Err(ParseError {
+                line: 1,
+                column: 12,
+                expected: "end of line".into(),
+                found: "`x`".into(),
+                snippet: "seeds: 1 2 x".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_malformed_list_items() {
+        let mut parser = Parser::new("1 2 3 4 5");
+        let error = parser
+            .many(|p| Ok((p.number::<u8>()?, p.number::<u8>()?)))
+            .unwrap_err();
+        assert_eq!(error.column, 10);
+        assert_eq!(error.expected, "a number");
+
+        let mut parser = Parser::new("1 2 x");
+        assert_eq!(parser.many(|p| p.number::<u8>()), Ok(vec![1, 2]));
+        assert_eq!(parser.rest(), " x");
+    }
+
+    #[test]
+    fn reports_overflowing_numbers() {
+        let mut parser = Parser::new("300");
+        let error = parser.number::<u8>().unwrap_err();
+        assert_eq!(error.column, 1);
+        assert_eq!(error.expected, "a number fitting in `u8`");
+    }
+
+    #[test]
+    fn splits_lines_and_sections() {
+        let input = "a: 1\nb: 2\n\n\nc: 3\r\nd: 4\n\ne: x\n";
+        let sections = Parser::new(input).sections();
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[1].rest(), "c: 3\r\nd: 4");
+
+        let lines: Vec<_> = sections[1].lines().collect();
+        assert_eq!(lines[1].rest(), "d: 4");
+        assert_eq!(lines[1].line(), 6);
+
+        let mut last = sections[2];
+        let error = last
+            .key_value(":", |p| p.word(), |p| p.number::<u32>())
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (8, 4));
+        assert_eq!(
+            error.to_string(),
+            "line 8, column 4: expected a number, found `x`\n  8 | e: x\n    |    ^"
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Deterministic property tests: run an optimized solution and a slow reference on many generated
+//! inputs, and shrink the first input they disagree on to a minimal one.
+//!
+//! Generators draw all their random choices from a [`Source`]. To shrink a failing input, the
+//! harness replays the generator with fewer and smaller choices, so generators don't need to know
+//! about shrinking. They only need smaller choices to give simpler inputs, which is the case when
+//! counts come from [`Source::below`] or [`Source::vec`] and items from [`Source::choose`].
+//!
+//! # Example
+//! ```
+//! use advent_of_code::helper::property::{Property, Source};
+//!
+//! let numbers = |source: &mut Source| source.vec(0..=10, |source| source.below(100));
+//! let failure = Property::new()
+//!     .find_failure(
+//!         numbers,
+//!         |numbers| numbers.iter().sum::<u64>().min(150),
+//!         |numbers| numbers.iter().sum::<u64>(),
+//!     )
+//!     .unwrap();
+//! // shrunk to a list summing to the smallest failing total.
+//! assert_eq!(failure.input.iter().sum::<u64>(), 151);
+//! ```
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+
+/// The random choices made by a generator, either drawn from a seeded generator or replayed.
+#[derive(Debug, Clone)]
+pub struct Source {
+    /// The choices made so far. When replaying, the choices still to make.
+    choices: Vec<u64>,
+    position: usize,
+    /// The state of the generator, `None` when replaying.
+    state: Option<u64>,
+}
+
+impl Source {
+    pub fn new(seed: u64) -> Self {
+        Source {
+            choices: Vec::new(),
+            position: 0,
+            state: Some(seed),
+        }
+    }
+
+    /// Replays `choices`: choices beyond the bound asked for are clamped, missing ones are 0.
+    fn replay(choices: Vec<u64>) -> Self {
+        Source {
+            choices,
+            position: 0,
+            state: None,
+        }
+    }
+
+    /// The choices actually made by the generator.
+    fn into_choices(mut self) -> Vec<u64> {
+        self.choices.truncate(self.position);
+        self.choices
+    }
+
+    /// splitmix64, whose state can be any value.
+    fn next_random(state: &mut u64) -> u64 {
+        *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number below `bound`, or 0 if `bound` is 0. Shrinks towards 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let max = bound.saturating_sub(1);
+        let choice = match self.state.as_mut() {
+            Some(state) => Source::next_random(state) % bound.max(1),
+            None => self.choices.get(self.position).map_or(0, |&c| c.min(max)),
+        };
+        // keeps the clamped choice when replaying.
+        match self.choices.get_mut(self.position) {
+            Some(replayed) => *replayed = choice,
+            None => self.choices.push(choice),
+        }
+        self.position += 1;
+        choice
+    }
+
+    /// A number in `range`. Shrinks towards the start of the range.
+    pub fn range(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (start, end) = range.into_inner();
+        let width = end.saturating_sub(start).saturating_add(1);
+        start + self.below(width).min(end.saturating_sub(start))
+    }
+
+    /// Shrinks towards `false`.
+    pub fn bool(&mut self) -> bool {
+        self.below(2) == 1
+    }
+
+    /// One of `items`, which must not be empty. Shrinks towards the first item.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// A vector with a length in `length`. Shrinks towards fewer, simpler items.
+    pub fn vec<T>(
+        &mut self,
+        length: RangeInclusive<usize>,
+        mut item: impl FnMut(&mut Self) -> T,
+    ) -> Vec<T> {
+        let (start, end) = length.into_inner();
+        let length = self.range(start as u64..=end as u64) as usize;
+        (0..length).map(|_| item(self)).collect()
+    }
+}
+
+/// An input on which the optimized function and the reference disagree, after shrinking.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure<T, R> {
+    /// The index of the generated input that failed first.
+    pub case: usize,
+    pub input: T,
+    pub optimized: R,
+    pub reference: R,
+    /// The number of shrinking steps which kept the input failing.
+    pub shrinks: usize,
+}
+
+/// The settings of a property test. Runs 200 cases from a fixed seed by default.
+#[derive(Debug, Clone, Copy)]
+pub struct Property {
+    cases: usize,
+    seed: u64,
+    /// The most inputs tried while shrinking, as the reference can be slow.
+    max_shrink_attempts: usize,
+}
+
+impl Default for Property {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Property {
+    pub fn new() -> Self {
+        Property {
+            cases: 200,
+            seed: 0x2545_f491_4f6c_dd1d,
+            max_shrink_attempts: 2000,
+        }
+    }
+
+    pub fn cases(self, cases: usize) -> Self {
+        Property { cases, ..self }
+    }
+
+    pub fn seed(self, seed: u64) -> Self {
+        Property { seed, ..self }
+    }
+
+    /// Runs `optimized` and `reference` on the generated inputs and panics with the smallest input
+    /// they disagree on.
+    pub fn check<T: Debug, R: PartialEq + Debug>(
+        &self,
+        generate: impl Fn(&mut Source) -> T,
+        optimized: impl Fn(&T) -> R,
+        reference: impl Fn(&T) -> R,
+    ) {
+        if let Some(failure) = self.find_failure(generate, optimized, reference) {
+            panic!(
+                "property failed on case {} of seed {:#x}, shrunk {} times\ninput: {:#?}\noptimized: {:?}\nreference: {:?}",
+                failure.case,
+                self.seed,
+                failure.shrinks,
+                failure.input,
+                failure.optimized,
+                failure.reference
+            );
+        }
+    }
+
+    /// Same as [`Property::check`], returning the shrunk failure instead of panicking.
+    pub fn find_failure<T, R: PartialEq>(
+        &self,
+        generate: impl Fn(&mut Source) -> T,
+        optimized: impl Fn(&T) -> R,
+        reference: impl Fn(&T) -> R,
+    ) -> Option<Failure<T, R>> {
+        // runs a source through the generator, keeping the input if it fails.
+        let run = |mut source: Source| {
+            let input = generate(&mut source);
+            let (optimized, reference) = (optimized(&input), reference(&input));
+            (optimized != reference).then(|| (source.into_choices(), input, optimized, reference))
+        };
+
+        let (case, failing) = (0..self.cases).find_map(|case| {
+            let seed = self.seed ^ (case as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            run(Source::new(seed)).map(|failing| (case, failing))
+        })?;
+        let (choices, shrinks) = shrink(failing.0, self.max_shrink_attempts, |candidate| {
+            run(Source::replay(candidate)).map(|(used, ..)| used)
+        });
+        // replaying is deterministic, and the shrunk choices fail.
+        let (_, input, optimized, reference) = run(Source::replay(choices))?;
+        Some(Failure {
+            case,
+            input,
+            optimized,
+            reference,
+            shrinks,
+        })
+    }
+}
+
+/// Shrinks failing `choices`, first by removing choices, then by making them smaller. `fails`
+/// returns the choices used by a candidate if it still fails. Tries at most `max_attempts`
+/// candidates, returns the shrunk choices and the number of times they were shrunk.
+fn shrink(
+    mut choices: Vec<u64>,
+    max_attempts: usize,
+    fails: impl Fn(Vec<u64>) -> Option<Vec<u64>>,
+) -> (Vec<u64>, usize) {
+    // a failing candidate is kept if it is simpler: fewer choices, or smaller ones.
+    let simpler = |candidate: Vec<u64>, choices: &Vec<u64>| {
+        let used = fails(candidate)?;
+        ((used.len(), &used) < (choices.len(), choices)).then_some(used)
+    };
+    let mut shrinks = 0;
+    let mut attempts = 0;
+    let mut improved = true;
+    while improved && attempts < max_attempts {
+        improved = false;
+        // fewer choices first: shorter inputs.
+        for chunk in [8, 4, 2, 1] {
+            let mut start = 0;
+            while start + chunk <= choices.len() && attempts < max_attempts {
+                let mut candidate = choices.clone();
+                candidate.drain(start..start + chunk);
+                attempts += 1;
+                match simpler(candidate, &choices) {
+                    Some(used) => {
+                        choices = used;
+                        shrinks += 1;
+                        improved = true;
+                    }
+                    None => start += 1,
+                }
+            }
+        }
+        // then smaller choices: simpler items.
+        let mut index = 0;
+        while index < choices.len() && attempts < max_attempts {
+            let choice = choices[index];
+            for smaller in [0, choice / 2, choice.saturating_sub(1)] {
+                if smaller >= choice {
+                    continue;
+                }
+                let mut candidate = choices.clone();
+                candidate[index] = smaller;
+                attempts += 1;
+                if let Some(used) = simpler(candidate, &choices) {
+                    choices = used;
+                    shrinks += 1;
+                    improved = true;
+                    break;
+                }
+            }
+            index += 1;
+        }
+    }
+    (choices, shrinks)
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{Property, Source};
+
+    #[test]
+    fn sources_are_deterministic() {
+        let draw = |seed| {
+            let mut source = Source::new(seed);
+            (0..20).map(|_| source.below(1000)).collect::<Vec<_>>()
+        };
+        assert_eq!(draw(1), draw(1));
+        assert_ne!(draw(1), draw(2));
+        assert!(draw(3).iter().all(|&value| value < 1000));
+
+        let mut source = Source::new(4);
+        assert_eq!(source.below(0), 0);
+        assert!((5..=7).contains(&source.range(5..=7)));
+        assert!(source.range(0..=u64::MAX) <= u64::MAX);
+        assert_eq!(source.choose(&['x']), &'x');
+    }
+
+    #[test]
+    fn replays_clamped_choices() {
+        let mut source = Source::replay(vec![5, 100]);
+        assert_eq!(source.below(10), 5);
+        assert_eq!(source.below(10), 9);
+        assert_eq!(source.below(10), 0);
+        assert_eq!(source.into_choices(), vec![5, 9, 0]);
+    }
+
+    #[test]
+    fn passing_property() {
+        let failure = Property::new().cases(50).find_failure(
+            |source| source.vec(0..=10, |source| source.below(100)),
+            |numbers| numbers.iter().rev().sum::<u64>(),
+            |numbers| numbers.iter().sum::<u64>(),
+        );
+        assert_eq!(failure, None);
+    }
+
+    #[test]
+    fn shrinks_failures() {
+        // fails as soon as the list isn't sorted.
+        let sorted = |numbers: &Vec<u64>| {
+            let mut sorted = numbers.clone();
+            sorted.sort();
+            sorted
+        };
+        let failure = Property::new()
+            .find_failure(
+                |source| source.vec(0..=20, |source| source.below(1000)),
+                |numbers| numbers.clone(),
+                sorted,
+            )
+            .unwrap();
+        assert_eq!(failure.input, vec![1, 0]);
+        assert_eq!(failure.reference, vec![0, 1]);
+        assert!(failure.shrinks > 0);
+
+        let again = Property::new()
+            .find_failure(
+                |source| source.vec(0..=20, |source| source.below(1000)),
+                |numbers| numbers.clone(),
+                sorted,
+            )
+            .unwrap();
+        assert_eq!(again, failure);
+    }
+
+    #[test]
+    #[should_panic(expected = "property failed on case 0")]
+    fn check_panics_with_input() {
+        Property::new().check(|source| source.below(10), |_| 1, |_| 2);
+    }
+}
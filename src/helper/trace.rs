@@ -0,0 +1,116 @@
+//! Leveled debug output for solutions.
+//!
+//! The level is read from the arguments of the solution binary: `--verbose` (or `-v`) enables
+//! [`Level::Debug`], passing it twice enables [`Level::Trace`]. `cargo solve` forwards these flags.
+//!
+//! Output goes to stderr so it never mixes with the results parsed by `cargo all`. It is disabled
+//! when benchmarking with `--time`, like every [`flags`], and compiled out entirely in release
+//! builds.
+use std::fmt::Debug;
+use std::sync::OnceLock;
+
+use crate::template::flags;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Debug,
+    Trace,
+}
+
+static LEVEL: OnceLock<Level> = OnceLock::new();
+
+/// The level of the current process, read from its arguments on first use.
+pub fn level() -> Level {
+    *LEVEL.get_or_init(|| level_from_verbosity(flags::count(&["-v", "--verbose"])))
+}
+
+/// Whether output at `level` should be printed.
+pub fn enabled(level: Level) -> bool {
+    cfg!(debug_assertions) && level != Level::Off && self::level() >= level
+}
+
+fn level_from_verbosity(verbosity: usize) -> Level {
+    match verbosity {
+        0 => Level::Off,
+        1 => Level::Debug,
+        _ => Level::Trace,
+    }
+}
+
+/// Prints to stderr if the debug level is enabled.
+///
+/// # Example
+/// ```
+/// advent_of_code::debug!("parsed {} lines", 42);
+/// ```
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions)
+            && $crate::helper::trace::enabled($crate::helper::trace::Level::Debug)
+        {
+            eprintln!("[debug] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Prints to stderr if the trace level is enabled.
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if cfg!(debug_assertions)
+            && $crate::helper::trace::enabled($crate::helper::trace::Level::Trace)
+        {
+            eprintln!("[trace] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Helper function that prints a debug representation of the given item and returns it,
+/// if the debug level is enabled.
+/// Useful for debugging in the middle of a chain of function calls within an iterator.
+///
+/// # Example
+/// ```
+/// use advent_of_code::helper::debug_item;
+///
+/// let v = vec![1, 2, 3, 4, 5];
+/// let sum = v.iter()
+///    .map(|x| x * 2)
+///    .map(debug_item)
+///    .sum::<i32>();
+///```
+pub fn debug_item<T>(item: T) -> T
+where
+    T: Debug,
+{
+    if enabled(Level::Debug) {
+        eprintln!("[debug] {:#?}", item);
+    }
+    item
+}
+
+/// Same as [`debug_item`], for the trace level.
+pub fn trace_item<T>(item: T) -> T
+where
+    T: Debug,
+{
+    if enabled(Level::Trace) {
+        eprintln!("[trace] {:#?}", item);
+    }
+    item
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::{level_from_verbosity, Level};
+
+    #[test]
+    fn reads_level_from_verbosity() {
+        assert_eq!(level_from_verbosity(0), Level::Off);
+        assert_eq!(level_from_verbosity(1), Level::Debug);
+        assert_eq!(level_from_verbosity(2), Level::Trace);
+        assert_eq!(level_from_verbosity(3), Level::Trace);
+    }
+}
@@ -1,4 +1,4 @@
-use advent_of_code::template::commands::{all, download, read, scaffold, solve};
+use advent_of_code::template::commands::{all, download, input_gen, read, scaffold, solve};
 use args::{parse, AppArguments};
 
 mod args {
@@ -22,11 +22,20 @@ mod args {
             time: bool,
             dhat: bool,
             submit: Option<u8>,
+            verbosity: u8,
+            dump_dot: bool,
+            scale: bool,
         },
         All {
             release: bool,
             time: bool,
         },
+        InputGen {
+            day: Day,
+            size: usize,
+            seed: u64,
+            output: Option<String>,
+        },
     }
 
     pub fn parse() -> Result<AppArguments, Box<dyn std::error::Error>> {
@@ -53,6 +62,21 @@ mod args {
                 submit: args.opt_value_from_str("--submit")?,
                 time: args.contains("--time"),
                 dhat: args.contains("--dhat"),
+                verbosity: {
+                    let mut verbosity = 0;
+                    while args.contains(["-v", "--verbose"]) {
+                        verbosity += 1;
+                    }
+                    verbosity
+                },
+                dump_dot: args.contains("--dump-dot"),
+                scale: args.contains("--scale"),
+            },
+            Some("input-gen") => AppArguments::InputGen {
+                day: args.free_from_str()?,
+                size: args.opt_value_from_str("--size")?.unwrap_or(1000),
+                seed: args.opt_value_from_str("--seed")?.unwrap_or(1),
+                output: args.opt_value_from_str("--output")?,
             },
             Some(x) => {
                 eprintln!("Unknown command: {x}");
@@ -95,7 +119,16 @@ fn main() {
                 time,
                 dhat,
                 submit,
-            } => solve::handle(day, release, time, dhat, submit),
+                verbosity,
+                dump_dot,
+                scale,
+            } => solve::handle(day, release, time, dhat, submit, verbosity, dump_dot, scale),
+            AppArguments::InputGen {
+                day,
+                size,
+                seed,
+                output,
+            } => input_gen::handle(day, size, seed, output),
         },
     };
 }
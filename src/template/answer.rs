@@ -0,0 +1,115 @@
+//! The answer of a puzzle part, as printed by the runner and submitted to Advent of Code.
+//!
+//! # Example
+//! ```
+//! use advent_of_code::template::Answer;
+//!
+//! assert_eq!(Answer::from(42_u64), Answer::Int(42));
+//! assert_eq!(Answer::from(-3_i64).to_string(), "-3");
+//! assert_eq!(Answer::from("ABC").to_string(), "ABC");
+//! assert!(Answer::multiline("#.#\n.#.").is_multiline());
+//! ```
+use std::fmt::Display;
+
+/// Solutions return any integer type, a [`String`] or an [`Answer`] directly. Prefer wide integers
+/// (`u64`, `u128` or `i64`) for answers that are sums or products: puzzle answers often outgrow
+/// `u32`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Int(i128),
+    String(String),
+    /// Printed below the part name, like letters drawn on a grid.
+    Multiline(String),
+}
+
+impl Answer {
+    pub fn multiline(text: &str) -> Self {
+        Answer::Multiline(text.to_string())
+    }
+
+    /// Whether the answer needs its own lines when printed.
+    pub fn is_multiline(&self) -> bool {
+        match self {
+            Answer::Multiline(_) => true,
+            Answer::String(text) => text.contains('\n'),
+            Answer::Int(_) => false,
+        }
+    }
+}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Int(value) => write!(f, "{value}"),
+            Answer::String(text) | Answer::Multiline(text) => write!(f, "{text}"),
+        }
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Answer {
+                fn from(value: $int) -> Self {
+                    Answer::Int(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::from(value as u64)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Self {
+        Answer::from(value as i64)
+    }
+}
+
+impl From<u128> for Answer {
+    /// Values above [`i128::MAX`] are kept as their decimal digits.
+    fn from(value: u128) -> Self {
+        i128::try_from(value).map_or_else(|_| Answer::String(value.to_string()), Answer::Int)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(text: String) -> Self {
+        Answer::String(text)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(text: &str) -> Self {
+        Answer::String(text.to_string())
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::Answer;
+
+    #[test]
+    fn converts_integers() {
+        assert_eq!(Answer::from(7_u32), Answer::Int(7));
+        assert_eq!(Answer::from(-7_i64), Answer::Int(-7));
+        assert_eq!(Answer::from(usize::MAX).to_string(), usize::MAX.to_string());
+        assert_eq!(Answer::from(u64::MAX as u128), Answer::Int(u64::MAX.into()));
+        assert_eq!(Answer::from(u128::MAX).to_string(), u128::MAX.to_string());
+    }
+
+    #[test]
+    fn multiline_answers() {
+        assert!(!Answer::from("ABC").is_multiline());
+        assert!(Answer::from("A\nB".to_string()).is_multiline());
+        assert!(Answer::multiline("ABC").is_multiline());
+        assert_eq!(Answer::multiline("#.\n.#").to_string(), "#.\n.#");
+        assert!(!Answer::Int(1).is_multiline());
+    }
+}
@@ -0,0 +1,23 @@
+use std::{fs, process};
+
+use crate::template::{input_gen, Day};
+
+pub fn handle(day: Day, size: usize, seed: u64, output: Option<String>) {
+    let Some(input) = input_gen::generate(day, size, seed) else {
+        eprintln!(
+            "No input generator for day {day}, days with one: {:?}.",
+            input_gen::DAYS
+        );
+        process::exit(1);
+    };
+
+    let path = output.unwrap_or_else(|| format!("data/inputs/{day}-{size}-{seed}.txt"));
+    if let Err(e) = fs::write(&path, input) {
+        eprintln!("Failed to write input file: {e}");
+        process::exit(1);
+    }
+
+    println!("Generated input of size {size} with seed {seed} in \"{path}\"");
+    println!("---");
+    println!("🎄 Run your solution on it by copying it to \"data/inputs/{day}.txt\".");
+}
@@ -13,6 +13,8 @@ fn safe_create_file(path: &str) -> Result<File, std::io::Error> {
     OpenOptions::new().write(true).create_new(true).open(path)
 }
 
+// existing inputs and examples are kept as they are, so scaffolding a day again doesn't wipe them.
+#[allow(clippy::suspicious_open_options)]
 fn create_file(path: &str) -> Result<File, std::io::Error> {
     OpenOptions::new().write(true).create(true).open(path)
 }
@@ -2,7 +2,17 @@ use std::process::{Command, Stdio};
 
 use crate::template::Day;
 
-pub fn handle(day: Day, release: bool, time: bool, dhat: bool, submit_part: Option<u8>) {
+#[allow(clippy::too_many_arguments)]
+pub fn handle(
+    day: Day,
+    release: bool,
+    time: bool,
+    dhat: bool,
+    submit_part: Option<u8>,
+    verbosity: u8,
+    dump_dot: bool,
+    scale: bool,
+) {
     let mut cmd_args = vec!["run".to_string(), "--bin".to_string(), day.to_string()];
 
     if dhat {
@@ -12,7 +22,7 @@ pub fn handle(day: Day, release: bool, time: bool, dhat: bool, submit_part: Opti
             "--features".to_string(),
             "dhat-heap".to_string(),
         ]);
-    } else if release {
+    } else if release || scale {
         cmd_args.push("--release".to_string());
     }
 
@@ -27,6 +37,18 @@ pub fn handle(day: Day, release: bool, time: bool, dhat: bool, submit_part: Opti
         cmd_args.push("--time".to_string());
     }
 
+    for _ in 0..verbosity {
+        cmd_args.push("--verbose".to_string());
+    }
+
+    if dump_dot {
+        cmd_args.push("--dump-dot".to_string());
+    }
+
+    if scale {
+        cmd_args.push("--scale".to_string());
+    }
+
     let mut cmd = Command::new("cargo")
         .args(&cmd_args)
         .stdout(Stdio::inherit())
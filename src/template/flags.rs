@@ -0,0 +1,55 @@
+//! Flags that `cargo solve` forwards to solution binaries to debug or profile them, like
+//! `--verbose`, `--dump-dot` or `--scale`.
+//!
+//! Benchmarking with `--time` turns all of them off, so that they never skew the timings.
+use std::env;
+
+/// How many times the solution binary was passed any of `names`, 0 with `--time`.
+pub fn count(names: &[&str]) -> usize {
+    count_in(env::args(), names)
+}
+
+/// Whether the solution binary was passed `name`, never with `--time`.
+pub fn is_set(name: &str) -> bool {
+    count(&[name]) > 0
+}
+
+fn count_in(args: impl Iterator<Item = String>, names: &[&str]) -> usize {
+    let mut count = 0;
+    for arg in args {
+        if arg == "--time" {
+            return 0;
+        }
+        if names.contains(&arg.as_str()) {
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use super::count_in;
+
+    fn count(args: &[&str], names: &[&str]) -> usize {
+        count_in(args.iter().map(|arg| arg.to_string()), names)
+    }
+
+    #[test]
+    fn counts_flags() {
+        let verbose = ["-v", "--verbose"];
+        assert_eq!(count(&["target/debug/01"], &verbose), 0);
+        assert_eq!(count(&["target/debug/01", "-v"], &verbose), 1);
+        assert_eq!(count(&["target/debug/01", "-v", "--verbose"], &verbose), 2);
+        assert_eq!(count(&["08", "--dump-dot"], &["--dump-dot"]), 1);
+        assert_eq!(count(&["08", "--dump-dot"], &["--scale"]), 0);
+    }
+
+    #[test]
+    fn time_disables_flags() {
+        assert_eq!(count(&["01", "-v", "--time"], &["-v"]), 0);
+        assert_eq!(count(&["01", "--time", "-v", "-v"], &["-v"]), 0);
+        assert_eq!(count(&["08", "--dump-dot", "--time"], &["--dump-dot"]), 0);
+        assert_eq!(count(&["05", "--time", "--scale"], &["--scale"]), 0);
+    }
+}
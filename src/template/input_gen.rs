@@ -0,0 +1,219 @@
+//! Synthetic inputs of any size, to stress solutions far beyond puzzle scale.
+//!
+//! Inputs are drawn from a seeded [`Source`], so a day, size and seed always give the same input.
+//! The size is the number of lines or records, whichever drives the input of a day:
+//!
+//! - day 3: schematic rows, 140 columns wide.
+//! - day 5: seed ranges and ranges per map, spread over the `u32` range so that seed ranges are huge.
+//! - day 7: distinct hands.
+//! - day 8: network nodes, split between up to 6 ghosts going around cycles.
+//!
+//! # Example
+//! ```
+//! use advent_of_code::day;
+//! use advent_of_code::template::input_gen::generate;
+//!
+//! let input = generate(day!(7), 1000, 42).unwrap();
+//! assert_eq!(input.lines().count(), 1000);
+//! assert_eq!(generate(day!(7), 1000, 42), Some(input));
+//! assert_eq!(generate(day!(1), 1000, 42), None);
+//! ```
+use std::collections::HashSet;
+
+use crate::helper::property::Source;
+use crate::template::Day;
+
+/// The days that have an input generator.
+pub const DAYS: [u8; 4] = [3, 5, 7, 8];
+
+/// Generates an input of `size` lines or records for `day`, `None` if the day has no generator.
+pub fn generate(day: Day, size: usize, seed: u64) -> Option<String> {
+    let source = &mut Source::new(seed);
+    match day.into_inner() {
+        3 => Some(schematic(source, size)),
+        5 => Some(almanac(source, size)),
+        7 => Some(hands(source, size)),
+        8 => Some(network(source, size)),
+        _ => None,
+    }
+}
+
+/// Rows of numbers and symbols. Numbers are separated by at least one `.`.
+fn schematic(source: &mut Source, rows: usize) -> String {
+    const WIDTH: usize = 140;
+    const SYMBOLS: [char; 10] = ['*', '#', '+', '$', '/', '@', '=', '%', '&', '-'];
+    let mut input = String::with_capacity(rows * (WIDTH + 1));
+    for _ in 0..rows {
+        let mut column = 0;
+        while column < WIDTH {
+            let digits = source.range(1..=3) as u32;
+            match source.below(10) {
+                0..=2 if column + (digits as usize) < WIDTH => {
+                    let number = source.range(10_u64.pow(digits - 1)..=10_u64.pow(digits) - 1);
+                    input.push_str(&format!("{number}."));
+                    column += digits as usize + 1;
+                }
+                3 => {
+                    input.push(*source.choose(&SYMBOLS));
+                    column += 1;
+                }
+                _ => {
+                    input.push('.');
+                    column += 1;
+                }
+            }
+        }
+        input.push('\n');
+    }
+    input
+}
+
+/// Seven maps from seeds to locations, each with `size` ranges in random order.
+fn almanac(source: &mut Source, size: usize) -> String {
+    const CATEGORIES: [&str; 8] = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let size = size.max(1) as u64;
+    // each seed range and range of a map lies in its own slot, so ranges never overlap.
+    let slot = (1_u64 << 32) / size;
+
+    let mut input = String::from("seeds:");
+    for _ in 0..size {
+        let start = source.below(1 << 32);
+        let length = source.range(slot / 4..=slot.max(1));
+        input.push_str(&format!(" {start} {length}"));
+    }
+    for categories in CATEGORIES.windows(2) {
+        input.push_str(&format!("\n\n{}-to-{} map:", categories[0], categories[1]));
+        let mut ranges = (0..size)
+            .map(|index| {
+                let source_start = index * slot + source.below(slot / 2);
+                let length = source.range(1..=(slot / 2).max(1));
+                let destination = source.below((1 << 32) - length);
+                (destination, source_start, length)
+            })
+            .collect::<Vec<_>>();
+        shuffle(source, &mut ranges);
+        for (destination, source_start, length) in ranges {
+            input.push_str(&format!("\n{destination} {source_start} {length}"));
+        }
+    }
+    input.push('\n');
+    input
+}
+
+/// Distinct random hands with bids up to 1000, as in real inputs: with a repeated hand, the total
+/// winnings would depend on the order of equal hands. At most 13⁵ hands.
+fn hands(source: &mut Source, size: usize) -> String {
+    const CARDS: [char; 13] = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+    let size = size.min(CARDS.len().pow(5));
+    let mut drawn = HashSet::with_capacity(size);
+    let mut input = String::with_capacity(size * 10);
+    while drawn.len() < size {
+        let hand = (0..5).map(|_| *source.choose(&CARDS)).collect::<String>();
+        if drawn.insert(hand.clone()) {
+            input.push_str(&format!("{hand} {}\n", source.range(1..=1000)));
+        }
+    }
+    input
+}
+
+/// Ghosts starting at `..A` nodes, going around a cycle of nodes ending with one `..Z` node. The
+/// first ghost starts at `AAA` and ends at `ZZZ`, for part one.
+fn network(source: &mut Source, size: usize) -> String {
+    let size = size.max(2);
+    let ghosts = (size / 2).min(6);
+    let turns = source.vec(1..=size.min(300), |source| *source.choose(&['L', 'R']));
+    let mut input = turns.into_iter().collect::<String>();
+    input.push_str("\n\n");
+
+    let mut lines = Vec::with_capacity(size);
+    for ghost in 0..ghosts {
+        // a start, then a cycle of nodes ending with the end node.
+        let nodes = size / ghosts + usize::from(ghost < size % ghosts);
+        let label = |node: usize| match node {
+            0 if ghost == 0 => "AAA".to_string(),
+            0 => format!("N{ghost}A"),
+            _ if node == nodes - 1 && ghost == 0 => "ZZZ".to_string(),
+            _ if node == nodes - 1 => format!("N{ghost}Z"),
+            _ => format!("N{ghost}X{node}X"),
+        };
+        for node in 0..nodes {
+            let next = if node == nodes - 1 {
+                1.min(node)
+            } else {
+                node + 1
+            };
+            lines.push(format!(
+                "{} = ({}, {})",
+                label(node),
+                label(next),
+                label(next)
+            ));
+        }
+    }
+    shuffle(source, &mut lines);
+    input.push_str(&lines.join("\n"));
+    input.push('\n');
+    input
+}
+
+/// Fisher-Yates shuffle.
+fn shuffle<T>(source: &mut Source, items: &mut [T]) {
+    for index in (1..items.len()).rev() {
+        items.swap(index, source.below(index as u64 + 1) as usize);
+    }
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{generate, DAYS};
+    use crate::template::Day;
+
+    #[test]
+    fn generates_reproducible_inputs() {
+        for day in DAYS.map(|day| Day::new(day).unwrap()) {
+            let input = generate(day, 100, 1).unwrap();
+            assert_eq!(generate(day, 100, 1).as_ref(), Some(&input));
+            assert_ne!(generate(day, 100, 2).as_ref(), Some(&input));
+        }
+        assert_eq!(generate(Day::new(1).unwrap(), 100, 1), None);
+    }
+
+    #[test]
+    fn generates_inputs_of_the_chosen_size() {
+        let lines = |day, size| {
+            generate(Day::new(day).unwrap(), size, 1)
+                .unwrap()
+                .lines()
+                .count()
+        };
+        assert_eq!(lines(3, 50), 50);
+        assert_eq!(lines(7, 50), 50);
+        // the turns, a blank line and the nodes.
+        assert_eq!(lines(8, 50), 52);
+        // the seeds, then a blank line, a header and the ranges of each map.
+        assert_eq!(lines(5, 50), 1 + 7 * 52);
+
+        let schematic = generate(Day::new(3).unwrap(), 20, 1).unwrap();
+        assert!(schematic.lines().all(|line| line.len() == 140));
+    }
+
+    #[test]
+    fn generates_distinct_hands() {
+        let input = generate(Day::new(7).unwrap(), 10_000, 1).unwrap();
+        let hands: HashSet<&str> = input.lines().map(|line| &line[..5]).collect();
+        assert_eq!(hands.len(), 10_000);
+    }
+}
@@ -1,11 +1,16 @@
 use std::{env, fs};
 
+mod answer;
 pub mod aoc_cli;
 pub mod commands;
 mod day;
+pub mod flags;
+pub mod input_gen;
 pub mod readme_benchmarks;
 pub mod runner;
+pub mod scaling;
 
+pub use answer::Answer;
 pub use day::*;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
@@ -36,19 +41,37 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// With `--scale`, parts are timed on inputs of growing size instead, see [`scaling`].
+///
+/// Other implementations of a part can be listed as named variants, like
+/// `solution!(5, part_two => [naive, bisect])`. They are run after the part, must return the same
+/// answer, and their timings are compared in a table.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
-        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2]);
+        $crate::solution!(@impl $day, [part_one, 1, []] [part_two, 2, []]);
     };
     ($day:expr, 1) => {
-        $crate::solution!(@impl $day, [part_one, 1]);
+        $crate::solution!(@impl $day, [part_one, 1, []]);
     };
     ($day:expr, 2) => {
-        $crate::solution!(@impl $day, [part_two, 2]);
+        $crate::solution!(@impl $day, [part_two, 2, []]);
+    };
+    ($day:expr, part_one => [$($one:ident),* $(,)?] $(,)?) => {
+        $crate::solution!(@impl $day, [part_one, 1, [$($one),*]] [part_two, 2, []]);
+    };
+    ($day:expr, part_two => [$($two:ident),* $(,)?] $(,)?) => {
+        $crate::solution!(@impl $day, [part_one, 1, []] [part_two, 2, [$($two),*]]);
+    };
+    (
+        $day:expr,
+        part_one => [$($one:ident),* $(,)?],
+        part_two => [$($two:ident),* $(,)?] $(,)?
+    ) => {
+        $crate::solution!(@impl $day, [part_one, 1, [$($one),*]] [part_two, 2, [$($two),*]]);
     };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $( [$func:ident, $part:expr, [$($variant:ident),*]] )*) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -58,8 +81,18 @@ macro_rules! solution {
 
         fn main() {
             use $crate::template::runner::*;
+            if $crate::template::scaling::scale_requested() {
+                $( $crate::template::scaling::run_scaling($func, DAY, $part); )*
+                return;
+            }
             let input = $crate::template::read_file("inputs", DAY);
-            $( run_part($func, &input, DAY, $part); )*
+            $(
+                let variants = [
+                    (stringify!($func), $func as fn(_) -> _),
+                    $( (stringify!($variant), $variant as fn(_) -> _), )*
+                ];
+                run_part(&variants, input.as_str(), DAY, $part);
+            )*
         }
     };
 }
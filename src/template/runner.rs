@@ -1,6 +1,5 @@
 /// Encapsulates code that interacts with solution functions.
-use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
-use std::fmt::Display;
+use crate::template::{aoc_cli, Answer, Day, ANSI_ITALIC, ANSI_RESET};
 use std::hint::black_box;
 use std::io::{stdout, Write};
 use std::process::Output;
@@ -9,19 +8,73 @@ use std::{cmp, env, process};
 
 use super::ANSI_BOLD;
 
-pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+/// A named implementation of a part.
+pub type Variant<'a, I, T> = (&'a str, fn(I) -> Option<T>);
+
+/// Runs and prints one part. Parts can return any type converting into an [`Answer`], which
+/// tells what integer types to prefer.
+///
+/// `variants` are the named implementations of the part, the first one being the solution. Other
+/// variants are run after it, must return the same answer, and are compared in a table.
+pub fn run_part<I: Clone, T: Into<Answer>>(
+    variants: &[Variant<I, T>],
+    input: I,
+    day: Day,
+    part: u8,
+) {
     let part_str = format!("Part {part}");
+    let func = variants[0].1;
 
-    let (result, duration, samples) =
-        run_timed(func, input, |result| print_result(result, &part_str, ""));
+    let (result, duration, samples) = run_timed(
+        |input| func(input).map(Into::into),
+        input.clone(),
+        |result| print_result(result, &part_str, ""),
+    );
 
     print_result(&result, &part_str, &format_duration(&duration, samples));
 
+    if variants.len() > 1 {
+        compare_variants(variants, input, &result, duration);
+    }
+
     if let Some(result) = result {
         submit_result(result, day, part);
     }
 }
 
+/// Runs the other variants of a part, and prints their timings relative to the first one. Panics if
+/// a variant disagrees with the answer of the first one.
+fn compare_variants<I: Clone, T: Into<Answer>>(
+    variants: &[Variant<I, T>],
+    input: I,
+    answer: &Option<Answer>,
+    duration: Duration,
+) {
+    let width = variants
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    let print_row = |name: &str, variant_duration: Duration| {
+        let ratio = variant_duration.as_secs_f64() / duration.as_secs_f64().max(1e-9);
+        let time = format!("{variant_duration:.1?}");
+        print!("\r");
+        println!("  {name:<width$}  {time:>10}  {ratio:>8.2}x");
+    };
+
+    print_row(variants[0].0, duration);
+    for &(name, func) in &variants[1..] {
+        let (result, variant_duration, _) =
+            run_timed(|input| func(input).map(Into::into), input.clone(), |_| {});
+        assert_eq!(
+            &result, answer,
+            "variant `{name}` disagrees with `{}`",
+            variants[0].0
+        );
+        print_row(name, variant_duration);
+    }
+}
+
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
@@ -58,13 +111,8 @@ fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) ->
     print!(" > {ANSI_ITALIC}benching{ANSI_RESET}");
     let _ = stdout.flush();
 
-    let bench_iterations = cmp::min(
-        10000,
-        cmp::max(
-            Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10),
-            10,
-        ),
-    );
+    let bench_iterations =
+        (Duration::from_secs(1).as_nanos() / cmp::max(base_time.as_nanos(), 10)).clamp(10, 10000);
 
     let mut timers: Vec<Duration> = vec![];
 
@@ -99,12 +147,12 @@ fn format_duration(duration: &Duration, samples: u128) -> String {
     }
 }
 
-fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str) {
+fn print_result(result: &Option<Answer>, part: &str, duration_str: &str) {
     let is_intermediate_result = duration_str.is_empty();
 
     match result {
         Some(result) => {
-            if result.to_string().contains('\n') {
+            if result.is_multiline() {
                 let str = format!("{part}: ▼ {duration_str}");
                 if is_intermediate_result {
                     print!("{str}");
@@ -137,8 +185,8 @@ fn print_result<T: Display>(result: &Option<T>, part: &str, duration_str: &str)
 /// Parse the arguments passed to `solve` and try to submit one part of the solution if:
 ///  1. we are in `--release` mode.
 ///  2. aoc-cli is installed.
-fn submit_result<T: Display>(
-    result: T,
+fn submit_result(
+    result: Answer,
     day: Day,
     part: u8,
 ) -> Option<Result<Output, aoc_cli::AocCommandError>> {
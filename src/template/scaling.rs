@@ -0,0 +1,327 @@
+//! Runs a part on inputs of growing size to estimate how its running time scales.
+//!
+//! Days with an [`input_gen`] generator are run on generated inputs of 125 to 16000 lines or
+//! records, other days on prefixes of their real input, skipping prefixes the part fails to parse.
+//! Timings are fitted to a power law
+//! `time = c * size^exponent`, printed along with the closest complexity and a log-log plot:
+//!
+//! ```text
+//! Part 2 on generated inputs:
+//!     125: 52.1µs
+//!     ...
+//! Exponent: 1.98, closest to O(n²)
+//!  210.3ms ┤                                                *
+//!          │                                         *
+//!   ...
+//! ```
+use std::fmt::Display;
+use std::hint::black_box;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use crate::template::{flags, input_gen, read_file, Day, ANSI_BOLD, ANSI_RESET};
+
+/// The seed of generated inputs.
+const SEED: u64 = 1;
+/// Each size is timed for about this long, and larger sizes are skipped once a run takes longer.
+const TIME_PER_SIZE: Duration = Duration::from_millis(200);
+const MAX_RUN_TIME: Duration = Duration::from_secs(2);
+
+/// Whether `solve` was called with `--scale`. Always false when benchmarking with `--time`.
+pub fn scale_requested() -> bool {
+    flags::is_set("--scale")
+}
+
+/// Times `func` on inputs of growing size, then prints the fitted complexity and a plot.
+pub fn run_scaling<T>(func: impl Fn(&str) -> Option<T>, day: Day, part: u8) {
+    // inputs are generated when possible, real inputs are only read otherwise.
+    let real_input = input_gen::generate(day, 1, SEED)
+        .is_none()
+        .then(|| read_file("inputs", day));
+    let sizes = match &real_input {
+        None => (0..8).map(|k| 125 << k).collect(),
+        Some(input) => prefix_sizes(input.lines().count()),
+    };
+    let kind = match real_input {
+        None => "generated inputs",
+        Some(_) => "prefixes of the input",
+    };
+    println!("{ANSI_BOLD}Part {part}{ANSI_RESET} on {kind}:");
+
+    let mut points = Vec::new();
+    for size in sizes {
+        let input = match &real_input {
+            None => input_gen::generate(day, size, SEED).unwrap(),
+            Some(input) => input.split_inclusive('\n').take(size).collect(),
+        };
+        // a prefix can cut a record that spans several lines, and parts panic on what they can't
+        // parse.
+        if real_input.is_some() && !runs_without_panic(&func, &input) {
+            println!("{size:>8}: skipped, the part can't parse this prefix");
+            continue;
+        }
+        let duration = fastest_run(&func, &input);
+        println!("{size:>8}: {duration:.1?}");
+        points.push((size, duration));
+        if duration > MAX_RUN_TIME {
+            break;
+        }
+    }
+
+    match Fit::new(&points) {
+        Some(fit) => {
+            println!(
+                "Exponent: {:.2}, closest to {}",
+                fit.exponent, fit.complexity
+            );
+            print!("{}", plot(&points, &fit));
+        }
+        None => println!("Not enough sizes to fit a complexity."),
+    }
+}
+
+/// Prefix lengths halving from `lines` down to a 64th of it.
+fn prefix_sizes(lines: usize) -> Vec<usize> {
+    let mut sizes: Vec<usize> = (0..7)
+        .rev()
+        .map(|k| lines >> k)
+        .filter(|&n| n > 0)
+        .collect();
+    sizes.dedup();
+    sizes
+}
+
+/// Whether `func` returns on `input`, without printing the panic message if it panics.
+fn runs_without_panic<T>(func: &impl Fn(&str) -> Option<T>, input: &str) -> bool {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| black_box(func(input))));
+    panic::set_hook(hook);
+    result.is_ok()
+}
+
+/// The fastest of the runs made within [`TIME_PER_SIZE`], which is the least noisy.
+fn fastest_run<T>(func: &impl Fn(&str) -> Option<T>, input: &str) -> Duration {
+    let start = Instant::now();
+    let mut fastest = Duration::MAX;
+    for _ in 0..1000 {
+        let timer = Instant::now();
+        black_box(func(black_box(input)));
+        fastest = fastest.min(timer.elapsed());
+        if start.elapsed() > TIME_PER_SIZE {
+            break;
+        }
+    }
+    fastest
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Complexity {
+    Linear,
+    Linearithmic,
+    Quadratic,
+}
+
+impl Complexity {
+    const ALL: [Complexity; 3] = [
+        Complexity::Linear,
+        Complexity::Linearithmic,
+        Complexity::Quadratic,
+    ];
+
+    /// The expected running time for `size`, up to a constant.
+    fn time(self, size: f64) -> f64 {
+        match self {
+            Complexity::Linear => size,
+            Complexity::Linearithmic => size * size.log2().max(1.0),
+            Complexity::Quadratic => size * size,
+        }
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Complexity::Linear => write!(f, "O(n)"),
+            Complexity::Linearithmic => write!(f, "O(n log n)"),
+            Complexity::Quadratic => write!(f, "O(n²)"),
+        }
+    }
+}
+
+/// A power law fitted to timings by least squares in log-log space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fit {
+    pub exponent: f64,
+    /// The logarithm of the constant factor, in seconds.
+    pub intercept: f64,
+    /// The complexity whose shape is closest to the timings, whatever its constant factor.
+    pub complexity: Complexity,
+}
+
+impl Fit {
+    /// `None` with fewer than 3 distinct sizes.
+    pub fn new(points: &[(usize, Duration)]) -> Option<Self> {
+        if points.len() < 3 {
+            return None;
+        }
+        let logs: Vec<(f64, f64)> = points
+            .iter()
+            .map(|&(size, duration)| {
+                // a zero duration would have an infinite logarithm.
+                let seconds = duration.as_secs_f64().max(1e-9);
+                ((size as f64).ln(), seconds.ln())
+            })
+            .collect();
+        let count = logs.len() as f64;
+        let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / count;
+        let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / count;
+        let variance_x = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum::<f64>();
+        if variance_x == 0.0 {
+            return None;
+        }
+        let covariance = logs
+            .iter()
+            .map(|p| (p.0 - mean_x) * (p.1 - mean_y))
+            .sum::<f64>();
+        let exponent = covariance / variance_x;
+
+        // the constant factor of each complexity is the mean of the log ratios, so the best shape
+        // has the least variance of them.
+        let spread = |complexity: Complexity| {
+            let ratios: Vec<f64> = logs
+                .iter()
+                .map(|&(x, y)| y - complexity.time(x.exp()).ln())
+                .collect();
+            let mean = ratios.iter().sum::<f64>() / count;
+            ratios.iter().map(|r| (r - mean).powi(2)).sum::<f64>()
+        };
+        let complexity = Complexity::ALL
+            .into_iter()
+            .min_by(|&a, &b| spread(a).total_cmp(&spread(b)))?;
+        Some(Fit {
+            exponent,
+            intercept: mean_y - exponent * mean_x,
+            complexity,
+        })
+    }
+
+    fn seconds(&self, size: usize) -> f64 {
+        (self.intercept + self.exponent * (size as f64).ln()).exp()
+    }
+}
+
+/// A log-log plot of the timings as `*`, and of the fit as `·` where it doesn't overlap them.
+fn plot(points: &[(usize, Duration)], fit: &Fit) -> String {
+    const HEIGHT: usize = 8;
+    const COLUMN_WIDTH: usize = 7;
+
+    let seconds: Vec<f64> = points
+        .iter()
+        .map(|&(_, duration)| duration.as_secs_f64().max(1e-9))
+        .collect();
+    let fitted: Vec<f64> = points.iter().map(|&(size, _)| fit.seconds(size)).collect();
+    let min = seconds
+        .iter()
+        .chain(&fitted)
+        .copied()
+        .fold(f64::MAX, f64::min);
+    let max = seconds
+        .iter()
+        .chain(&fitted)
+        .copied()
+        .fold(f64::MIN, f64::max);
+    let row = |value: f64| match max / min {
+        ratio if ratio <= 1.0 => 0,
+        ratio => ((value / min).ln() / ratio.ln() * (HEIGHT - 1) as f64).round() as usize,
+    };
+
+    let mut grid = vec![vec![' '; points.len() * COLUMN_WIDTH]; HEIGHT];
+    for (index, (&time, &fitted)) in seconds.iter().zip(&fitted).enumerate() {
+        let column = index * COLUMN_WIDTH + COLUMN_WIDTH / 2;
+        grid[HEIGHT - 1 - row(fitted).min(HEIGHT - 1)][column] = '·';
+        grid[HEIGHT - 1 - row(time).min(HEIGHT - 1)][column] = '*';
+    }
+
+    let mut plot = String::new();
+    for (index, line) in grid.iter().enumerate() {
+        let line: String = line.iter().collect();
+        let label = match index {
+            0 => format!("{:.1?}", Duration::from_secs_f64(max)),
+            _ if index == HEIGHT - 1 => format!("{:.1?}", Duration::from_secs_f64(min)),
+            _ => String::new(),
+        };
+        let axis = if label.is_empty() { '│' } else { '┤' };
+        plot.push_str(&format!("{label:>9} {axis}{}\n", line.trim_end()));
+    }
+    plot.push_str(&format!(
+        "{:>9} └{}\n",
+        "",
+        "─".repeat(points.len() * COLUMN_WIDTH)
+    ));
+    let sizes: String = points
+        .iter()
+        .map(|&(size, _)| format!("{size:^COLUMN_WIDTH$}"))
+        .collect();
+    plot.push_str(&format!("{:>9}  {}\n", "", sizes.trim_end()));
+    plot
+}
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    use std::time::Duration;
+
+    use super::{plot, prefix_sizes, runs_without_panic, Complexity, Fit};
+
+    fn timings(time: impl Fn(f64) -> f64) -> Vec<(usize, Duration)> {
+        (0..8)
+            .map(|k| {
+                let size = 125 << k;
+                (size, Duration::from_secs_f64(time(size as f64) * 1e-9))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn fits_complexities() {
+        let fit = Fit::new(&timings(|n| 3.0 * n)).unwrap();
+        assert!((fit.exponent - 1.0).abs() < 0.01);
+        assert_eq!(fit.complexity, Complexity::Linear);
+
+        let fit = Fit::new(&timings(|n| n * n.log2())).unwrap();
+        assert!(fit.exponent > 1.05 && fit.exponent < 1.2);
+        assert_eq!(fit.complexity, Complexity::Linearithmic);
+
+        let fit = Fit::new(&timings(|n| 0.5 * n * n)).unwrap();
+        assert!((fit.exponent - 2.0).abs() < 0.01);
+        assert_eq!(fit.complexity, Complexity::Quadratic);
+
+        assert_eq!(Fit::new(&timings(|n| n)[..2]), None);
+        assert_eq!(Complexity::Quadratic.to_string(), "O(n²)");
+    }
+
+    #[test]
+    fn plots_timings() {
+        let points = timings(|n| n * n);
+        let plot = plot(&points, &Fit::new(&points).unwrap());
+        let lines: Vec<&str> = plot.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert!(lines[0].starts_with("  256.0ms ┤"));
+        assert!(lines[0].ends_with('*'));
+        assert!(lines[7].starts_with("   15.6µs ┤   *"));
+        assert!(lines[9].trim().starts_with("125    250"));
+    }
+
+    #[test]
+    fn halves_prefixes() {
+        assert_eq!(prefix_sizes(1000), vec![15, 31, 62, 125, 250, 500, 1000]);
+        assert_eq!(prefix_sizes(3), vec![1, 3]);
+    }
+
+    #[test]
+    fn detects_panicking_runs() {
+        let second_line = |input: &str| Some(input.lines().nth(1).unwrap().len());
+        assert!(runs_without_panic(&second_line, "Time: 7\nDistance: 9\n"));
+        assert!(!runs_without_panic(&second_line, "Time: 7\n"));
+    }
+}